@@ -0,0 +1,52 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::STDIN_PATH;
+
+/// Returns the files to search for the given paths, directories are only walked recursively
+/// when enabled and at most `max_depth` levels below the given directory
+pub fn collect_files(
+    paths: &[String],
+    recursive: bool,
+    max_depth: Option<usize>,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if !path.is_dir() || path == Path::new(STDIN_PATH) {
+            files.push(path.to_path_buf());
+        } else if recursive {
+            walk(path, 0, max_depth.unwrap_or(usize::MAX), &mut files)?;
+        } else {
+            bail!("{} is a directory", path.display());
+        }
+    }
+    Ok(files)
+}
+
+/// Adds the files of the directory in sorted order and descends into sub directories, symbolic
+/// links found in the directories are not followed
+fn walk(directory: &Path, depth: usize, max_depth: usize, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(directory)
+        .with_context(|| format!("failed to read directory {}", directory.display()))?
+        .map(|entry| entry.and_then(|entry| Ok((entry.path(), entry.file_type()?))))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (entry, file_type) in entries {
+        // symbolic links are skipped like grep -r does, links to a parent would never end
+        if file_type.is_symlink() {
+            continue;
+        }
+        if !file_type.is_dir() {
+            files.push(entry);
+        } else if depth < max_depth {
+            walk(&entry, depth + 1, max_depth, files)?;
+        }
+    }
+    Ok(())
+}
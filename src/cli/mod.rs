@@ -0,0 +1,450 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use grep_starter_rust::Regex;
+use grep_starter_rust::RegexBuilder;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+
+mod files;
+#[cfg(test)]
+mod test;
+
+/// Graphic rendition of highlighted matches if no other is configured, bold red
+const DEFAULT_COLOR_CODE: &str = "01;31";
+/// File argument that reads the standard input instead
+const STDIN_PATH: &str = "-";
+/// Name of the standard input in prefixes of selected lines
+const STDIN_NAME: &str = "(standard input)";
+/// Capacity in bytes of the buffers files and the standard input are read with by default
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+/// Short flags without a value that can be combined into one argument, e.g. -ob for -o -b
+const COMBINABLE_FLAGS: &str = "EFGdzcrolLbvwq";
+
+/// Command line options of the grep program
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Patterns to search for, a line is selected if any of them matches. A pattern `-` is
+    /// replaced by the first line of the standard input, the rest of it is the input
+    pub patterns: Vec<String>,
+    /// Prints debug logs in addition to the selected lines
+    pub debug: bool,
+    /// Separates lines by a NUL byte instead of a newline, e.g. for `find -print0`
+    pub null_data: bool,
+    /// Interprets the patterns as basic instead of extended regular expressions
+    pub basic: bool,
+    /// Searches for the patterns as literal strings instead of regular expressions
+    pub fixed_strings: bool,
+    /// Only selects lines in which the patterns match whole words
+    pub word_regexp: bool,
+    /// Highlights the matched text of selected lines with ANSI escape sequences
+    pub color: bool,
+    /// Selects the graphic rendition used for highlighting, e.g. `32` for green
+    pub color_code: Option<String>,
+    /// Prints every non empty match as JSON object with its line number, offsets and text (JSON
+    /// Lines), and the file name when lines are prefixed with it
+    pub json: bool,
+    /// Lets ^ and $ match at newlines inside of a record, this only matters if records may
+    /// contain newlines, i.e. together with `-z`
+    pub multiline: bool,
+    /// Files to search instead of the standard input
+    pub files: Vec<String>,
+    /// Searches all files in the directories given as files and their sub directories
+    pub recursive: bool,
+    /// Limits how many levels below the given directories are searched, 0 only searches
+    /// the files directly inside of them
+    pub max_depth: Option<usize>,
+    /// Prints the number of selected lines instead of the lines
+    pub count: bool,
+    /// Prints the total number of matches instead of the lines, which may be more than one per line
+    pub count_matches: bool,
+    /// Selects the lines that do not match instead, `-c` counts them and `-o` prints nothing
+    /// as they contain no matches
+    pub invert_match: bool,
+    /// Prints every non empty match on its own line instead of the whole selected lines
+    pub only_matching: bool,
+    /// Prefixes selected lines (or matches with `-o`) with their byte offset in the input
+    pub byte_offset: bool,
+    /// Skips lines longer than this many bytes with a warning instead of matching them,
+    /// e.g. to not spend a long time on a single huge line of minified or binary data
+    pub max_line_length: Option<usize>,
+    /// Capacity in bytes of the read buffers, larger buffers need fewer reads of huge inputs
+    pub buffer_size: Option<usize>,
+    /// Prints nothing and stops reading at the first selected line, only the exit status tells
+    /// if a line was selected
+    pub quiet: bool,
+    /// Prints only the names of files with a selected line, stopping at their first match
+    pub files_with_matches: bool,
+    /// Prints only the names of files without any selected line
+    pub files_without_match: bool,
+}
+
+impl Options {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options> {
+        let mut options = Options::default();
+        let mut positional = Vec::new();
+
+        let mut args = args.into_iter();
+        let mut combined = Vec::new();
+        while let Some(arg) = combined.pop().or_else(|| args.next()) {
+            match arg.as_str() {
+                "-E" | "--extended-regexp" => options.basic = false,
+                "-G" | "--basic-regexp" => options.basic = true,
+                "-F" | "--fixed-strings" => options.fixed_strings = true,
+                "-w" | "--word-regexp" => options.word_regexp = true,
+                "-d" | "--debug" => options.debug = true,
+                "-z" | "--null-data" => options.null_data = true,
+                "--json" => options.json = true,
+                "--multiline" => options.multiline = true,
+                "-c" | "--count" => options.count = true,
+                "--count-matches" => options.count_matches = true,
+                "-v" | "--invert-match" => options.invert_match = true,
+                "-o" | "--only-matching" => options.only_matching = true,
+                "-b" | "--byte-offset" => options.byte_offset = true,
+                "-q" | "--quiet" => options.quiet = true,
+                "-l" | "--files-with-matches" => options.files_with_matches = true,
+                "-L" | "--files-without-match" => options.files_without_match = true,
+                "-r" | "--recursive" => options.recursive = true,
+                _ if arg.starts_with("--max-depth=") => {
+                    let depth = &arg["--max-depth=".len()..];
+                    options.max_depth = Some(depth.parse().context("invalid --max-depth")?)
+                }
+                _ if arg.starts_with("--max-line-length=") => {
+                    let length = &arg["--max-line-length=".len()..];
+                    let length = length.parse().context("invalid --max-line-length")?;
+                    options.max_line_length = Some(length)
+                }
+                _ if arg.starts_with("--buffer-size=") => {
+                    let size = &arg["--buffer-size=".len()..];
+                    let size = size.parse().context("invalid --buffer-size")?;
+                    if size == 0 {
+                        bail!("--buffer-size must be at least 1");
+                    }
+                    options.buffer_size = Some(size)
+                }
+                "--color" | "--color=always" => options.color = true,
+                "--color=never" => options.color = false,
+                _ if arg.starts_with("--color-code=") => {
+                    options.color_code = Some(arg["--color-code=".len()..].to_string())
+                }
+                "-e" => options
+                    .patterns
+                    .push(args.next().context("no pattern found after -e")?),
+                _ if arg.len() > 2
+                    && arg.starts_with('-')
+                    && arg[1..].chars().all(|c| COMBINABLE_FLAGS.contains(c)) =>
+                {
+                    combined.extend(arg[1..].chars().rev().map(|c| format!("-{}", c)));
+                }
+                _ if arg.len() > 1 && arg.starts_with('-') => bail!("Unknown option {}", arg),
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        if options.patterns.is_empty() {
+            options
+                .patterns
+                .push(positional.next().context("no pattern found")?);
+        }
+        options.files.extend(positional);
+        if options.recursive && options.files.is_empty() {
+            options.files.push(".".to_string());
+        }
+
+        Ok(options)
+    }
+
+    fn color_code(&self) -> &str {
+        self.color_code.as_deref().unwrap_or(DEFAULT_COLOR_CODE)
+    }
+
+    fn buffer_size(&self) -> usize {
+        self.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE)
+    }
+
+    fn line_terminator(&self) -> u8 {
+        if self.null_data {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/// Prints all lines of the files (or the input if there are none) that match any of the
+/// patterns and returns if a line was selected, the input is read through a buffer of the
+/// configured size
+pub fn run<R: Read, W: Write>(options: &Options, input: R, output: &mut W) -> Result<bool> {
+    if options.debug {
+        writeln!(output, "Logs from your program will appear here!")?;
+    }
+
+    let mut input = BufReader::with_capacity(options.buffer_size(), input);
+    let patterns = read_patterns(options, &mut input)?;
+    let regex = RegexBuilder::any(&patterns)
+        .line_terminator(options.line_terminator())
+        .basic(options.basic)
+        .fixed_strings(options.fixed_strings)
+        .whole_words(options.word_regexp)
+        .multi_line(options.multiline)
+        .build()?;
+
+    if options.files.is_empty() {
+        return search(options, &regex, input, STDIN_NAME, false, output);
+    }
+
+    let files = files::collect_files(&options.files, options.recursive, options.max_depth)?;
+    // lines are prefixed with their file name as soon as more than one file may be searched
+    let with_name = options.recursive || files.len() > 1;
+    let mut selected = false;
+    for path in files {
+        // the exit status is known after the first selected line
+        if selected && options.quiet {
+            break;
+        }
+        // - stands for the standard input as in grep
+        if path.as_os_str() == STDIN_PATH {
+            selected |= search(options, &regex, &mut input, STDIN_NAME, with_name, output)?;
+            continue;
+        }
+
+        let file =
+            File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+        let name = path.display().to_string();
+        selected |= search(
+            options,
+            &regex,
+            BufReader::with_capacity(options.buffer_size(), file),
+            &name,
+            with_name,
+            output,
+        )?;
+    }
+    Ok(selected)
+}
+
+/// Returns the patterns with every `-` replaced by the next line of the input
+fn read_patterns<R: BufRead>(options: &Options, input: &mut R) -> Result<Vec<String>> {
+    let terminator = options.line_terminator();
+    let mut patterns = Vec::with_capacity(options.patterns.len());
+    for pattern in &options.patterns {
+        if pattern != STDIN_PATH {
+            patterns.push(pattern.clone());
+            continue;
+        }
+
+        let mut line = Vec::new();
+        if input.read_until(terminator, &mut line)? == 0 {
+            bail!("no pattern found in the standard input");
+        }
+        if line.last() == Some(&terminator) {
+            line.pop();
+        }
+        patterns.push(to_line(line, terminator)?);
+    }
+    Ok(patterns)
+}
+
+/// Prints all lines of the input that match, prefixed by the name of the input if `with_name`
+/// is set, and returns if a line was selected (or with `-L` if the name was listed)
+fn search<R: BufRead, W: Write>(
+    options: &Options,
+    regex: &Regex,
+    input: R,
+    name: &str,
+    with_name: bool,
+    output: &mut W,
+) -> Result<bool> {
+    if options.quiet {
+        return first_match(options, regex, input, name);
+    }
+    if options.files_with_matches || options.files_without_match {
+        let listed = first_match(options, regex, input, name)? == options.files_with_matches;
+        if listed {
+            writeln!(output, "{}", name)?;
+        }
+        return Ok(listed);
+    }
+
+    let input_name = name;
+    let name = with_name.then_some(name);
+    let terminator = options.line_terminator();
+    let mut count = 0;
+    let mut next_offset = 0;
+    for (index, line) in input.split(terminator).enumerate() {
+        let line = line?;
+        let offset = next_offset;
+        next_offset += line.len() + 1;
+        let Ok(line) = to_line(line, terminator) else {
+            warn_invalid_line(input_name, index + 1);
+            break;
+        };
+        if !is_selected(options, regex, &line)? {
+            continue;
+        }
+        let offset = options.byte_offset.then_some(offset);
+
+        if options.count_matches {
+            count += regex.count(&line);
+            continue;
+        }
+        count += 1;
+        if options.count {
+            continue;
+        }
+
+        if options.json {
+            write_json_matches(output, regex, name, index + 1, &line)?;
+        } else if options.only_matching {
+            // empty matches are skipped instead of printing blank lines
+            for m in regex.find_iter(&line).filter(|m| m.start() < m.end()) {
+                write_prefix(output, name, offset.map(|offset| offset + m.start()))?;
+                output.write_all(m.as_str().as_bytes())?;
+                output.write_all(&[terminator])?;
+            }
+        } else {
+            write_prefix(output, name, offset)?;
+            if options.color {
+                write_highlighted(output, regex, &line, options.color_code())?;
+            } else {
+                output.write_all(line.as_bytes())?;
+            }
+            output.write_all(&[terminator])?;
+        }
+    }
+
+    if options.count || options.count_matches {
+        write_prefix(output, name, None)?;
+        writeln!(output, "{}", count)?;
+    }
+    Ok(count > 0)
+}
+
+/// Writes the name of the input and the byte offset if they are printed for each line
+fn write_prefix<W: Write>(output: &mut W, name: Option<&str>, offset: Option<usize>) -> Result<()> {
+    if let Some(name) = name {
+        write!(output, "{}:", name)?;
+    }
+    if let Some(offset) = offset {
+        write!(output, "{}:", offset)?;
+    }
+    Ok(())
+}
+
+/// Returns if any line of the input is selected, the rest of the input is not read
+fn first_match<R: BufRead>(options: &Options, regex: &Regex, input: R, name: &str) -> Result<bool> {
+    let terminator = options.line_terminator();
+    for (index, line) in input.split(terminator).enumerate() {
+        let Ok(line) = to_line(line?, terminator) else {
+            warn_invalid_line(name, index + 1);
+            break;
+        };
+        if is_selected(options, regex, &line)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Warns that the rest of the input is not searched, e.g. of binary files found by -r, as the
+/// line is not valid UTF-8, the lines before it were searched as usual
+fn warn_invalid_line(name: &str, line_number: usize) {
+    eprintln!(
+        "warning: stopped searching {} at line {}, it is not valid UTF-8",
+        name, line_number
+    );
+}
+
+/// Returns if the line matches, or with `-v` if it does not match, lines longer than the
+/// maximum line length are never selected
+fn is_selected(options: &Options, regex: &Regex, line: &str) -> Result<bool> {
+    if let Some(max) = options.max_line_length.filter(|max| line.len() > *max) {
+        eprintln!(
+            "warning: skipped a line of {} bytes, longer than --max-line-length={}",
+            line.len(),
+            max
+        );
+        return Ok(false);
+    }
+    Ok(regex.try_find(line)?.is_some() != options.invert_match)
+}
+
+/// Writes one JSON object per non empty match of the line, offsets are bytes into the line,
+/// the name of the input is included if it is set
+fn write_json_matches<W: Write>(
+    output: &mut W,
+    regex: &Regex,
+    name: Option<&str>,
+    line_number: usize,
+    line: &str,
+) -> Result<()> {
+    for m in regex.find_iter(line).filter(|m| m.start() < m.end()) {
+        write!(output, "{{")?;
+        if let Some(name) = name {
+            write!(output, "\"file\":{},", json_string(name))?;
+        }
+        writeln!(
+            output,
+            "\"line\":{},\"start\":{},\"end\":{},\"text\":{}}}",
+            line_number,
+            m.start(),
+            m.end(),
+            json_string(m.as_str())
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes the text as JSON string, escaping quotes, backslashes and control characters
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes the line with every match wrapped into the escape sequences of the color code
+fn write_highlighted<W: Write>(
+    output: &mut W,
+    regex: &Regex,
+    line: &str,
+    color_code: &str,
+) -> Result<()> {
+    let mut last = 0;
+    for m in regex.find_iter(line).filter(|m| m.start() < m.end()) {
+        write!(
+            output,
+            "{}\x1b[{}m{}\x1b[0m",
+            &line[last..m.start()],
+            color_code,
+            m.as_str()
+        )?;
+        last = m.end();
+    }
+    output.write_all(&line.as_bytes()[last..])?;
+    Ok(())
+}
+
+/// Converts a line split at the terminator to a string, like `BufRead::lines` a trailing
+/// carriage return of newline terminated lines is removed
+fn to_line(mut line: Vec<u8>, terminator: u8) -> Result<String> {
+    if terminator == b'\n' && line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(String::from_utf8(line)?)
+}
@@ -0,0 +1,470 @@
+use super::*;
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+    use std::process;
+
+    use super::*;
+
+    fn run_grep(args: &[&str], input: &str) -> (bool, String) {
+        let options = Options::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+        let mut output = Vec::new();
+        let selected = run(&options, Cursor::new(input), &mut output).unwrap();
+        (selected, String::from_utf8(output).unwrap())
+    }
+
+    /// Creates a fresh directory below the temporary directory with the files and their content
+    fn create_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = env::temp_dir().join(format!("grep-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn select_matching_lines() {
+        let (selected, output) = run_grep(&["-E", "\\d"], "apple\nbanana 1\n");
+        assert!(selected);
+        assert_eq!(output, "banana 1\n");
+    }
+
+    #[test]
+    fn select_no_lines() {
+        let (selected, output) = run_grep(&["-E", "\\d"], "apple\nbanana\n");
+        assert!(!selected);
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn select_lines_matching_any_pattern() {
+        let (selected, output) = run_grep(&["-e", "cat", "-e", "dog"], "cat\nbird\nhotdog\n");
+        assert!(selected);
+        assert_eq!(output, "cat\nhotdog\n");
+    }
+
+    #[test]
+    fn reject_missing_pattern() {
+        assert!(Options::parse(["-e".to_string()]).is_err());
+        assert!(Options::parse(["-E".to_string()]).is_err());
+    }
+
+    #[test]
+    fn print_debug_line_only_with_flag() {
+        let (_, output) = run_grep(&["-E", "a"], "apple\n");
+        assert_eq!(output, "apple\n");
+
+        let (_, output) = run_grep(&["-d", "-E", "a"], "apple\n");
+        assert_eq!(output, "Logs from your program will appear here!\napple\n");
+
+        let (_, output) = run_grep(&["--debug", "-E", "a"], "apple\n");
+        assert!(output.starts_with("Logs from your program will appear here!\n"));
+    }
+
+    #[test]
+    fn select_null_separated_records() {
+        let input = "apple\0banana 1\0cherry 2\0";
+        let (selected, output) = run_grep(&["-z", "-E", "\\d$"], input);
+        assert!(selected);
+        assert_eq!(output, "banana 1\0cherry 2\0");
+    }
+
+    #[test]
+    fn select_null_separated_records_with_newlines() {
+        let input = "first\nsecond\0second\0";
+        let (_, output) = run_grep(&["--null-data", "-E", "^second$"], input);
+        assert_eq!(output, "second\0");
+    }
+
+    #[test]
+    fn select_wildcard_across_newline_in_null_data() {
+        let (_, output) = run_grep(&["-z", "-E", "a.b"], "a\nb\0a\0b\0");
+        assert_eq!(output, "a\nb\0");
+    }
+
+    #[test]
+    fn select_basic_and_extended_repetition() {
+        let input = "aaa\na+\n";
+        let (_, output) = run_grep(&["-G", "^a+$"], input);
+        assert_eq!(output, "a+\n");
+        let (_, output) = run_grep(&["-E", "^a+$"], input);
+        assert_eq!(output, "aaa\n");
+        let (_, output) = run_grep(&["-G", "^a\\+$"], input);
+        assert_eq!(output, "aaa\n");
+    }
+
+    #[test]
+    fn select_with_highlighted_matches() {
+        let (_, output) = run_grep(&["--color", "\\d+"], "a 1 b 22\nnone\n");
+        assert_eq!(output, "a \x1b[01;31m1\x1b[0m b \x1b[01;31m22\x1b[0m\n");
+    }
+
+    #[test]
+    fn select_with_custom_color_code() {
+        let (_, output) = run_grep(&["--color", "--color-code=32", "b+"], "abbc\n");
+        assert_eq!(output, "a\x1b[32mbb\x1b[0mc\n");
+        let (_, output) = run_grep(&["--color=never", "--color-code=32", "b+"], "abbc\n");
+        assert_eq!(output, "abbc\n");
+    }
+
+    #[test]
+    fn select_lines_starting_with_anchored_pattern() {
+        let input = "apple\nbanana\navocado\ncherry a\n";
+        let (_, output) = run_grep(&["^a"], input);
+        assert_eq!(output, "apple\navocado\n");
+        let (_, output) = run_grep(&["^a|^c"], input);
+        assert_eq!(output, "apple\navocado\ncherry a\n");
+    }
+
+    #[test]
+    fn select_matches_as_json_lines() {
+        let (selected, output) = run_grep(&["--json", "\\d+"], "none\na 1 b 22\n");
+        assert!(selected);
+        assert_eq!(
+            output,
+            "{\"line\":2,\"start\":2,\"end\":3,\"text\":\"1\"}\n\
+             {\"line\":2,\"start\":6,\"end\":8,\"text\":\"22\"}\n"
+        );
+    }
+
+    #[test]
+    fn select_matches_as_json_without_empty_matches_and_with_file_names() {
+        let (_, output) = run_grep(&["--json", "a*"], "baa\n");
+        assert_eq!(
+            output,
+            "{\"line\":1,\"start\":1,\"end\":3,\"text\":\"aa\"}\n"
+        );
+
+        let root = create_tree("json", &[("a.txt", "cat\n"), ("b.txt", "hotcat\n")]);
+        let a = root.join("a.txt").display().to_string();
+        let b = root.join("b.txt").display().to_string();
+        let (_, output) = run_grep(&["--json", "cat", &a, &b], "");
+        assert_eq!(
+            output,
+            format!(
+                "{{\"file\":\"{}\",\"line\":1,\"start\":0,\"end\":3,\"text\":\"cat\"}}\n\
+                 {{\"file\":\"{}\",\"line\":1,\"start\":3,\"end\":6,\"text\":\"cat\"}}\n",
+                a, b
+            )
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn select_matches_as_json_with_escaped_text() {
+        let (_, output) = run_grep(&["--json", "a.*"], "a\"\\\tb\n");
+        assert_eq!(
+            output,
+            "{\"line\":1,\"start\":0,\"end\":5,\"text\":\"a\\\"\\\\\\tb\"}\n"
+        );
+    }
+
+    #[test]
+    fn select_lines_of_files() {
+        let root = create_tree("files", &[("a.txt", "cat\ndog\n"), ("b.txt", "hotdog\n")]);
+        let a = root.join("a.txt").display().to_string();
+        let b = root.join("b.txt").display().to_string();
+
+        let (_, output) = run_grep(&["dog", &a], "dog from stdin\n");
+        assert_eq!(output, "dog\n");
+        let (_, output) = run_grep(&["dog", &a, &b], "");
+        assert_eq!(output, format!("{}:dog\n{}:hotdog\n", a, b));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn select_lines_recursively_up_to_max_depth() {
+        let root = create_tree(
+            "depth",
+            &[
+                ("top.txt", "match top\n"),
+                ("one/mid.txt", "match mid\n"),
+                ("one/two/deep.txt", "match deep\n"),
+            ],
+        );
+        let dir = root.display().to_string();
+        let name = |path: &str| root.join(path).display().to_string();
+
+        let (_, output) = run_grep(&["-r", "--max-depth=0", "match", &dir], "");
+        assert_eq!(output, format!("{}:match top\n", name("top.txt")));
+        let (_, output) = run_grep(&["-r", "--max-depth=1", "match", &dir], "");
+        assert_eq!(
+            output,
+            format!(
+                "{}:match mid\n{}:match top\n",
+                name("one/mid.txt"),
+                name("top.txt")
+            )
+        );
+        let (_, output) = run_grep(&["-r", "match", &dir], "");
+        assert_eq!(output.lines().count(), 3);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn select_lines_of_files_or_stdin() {
+        let root = create_tree("stdin", &[("a.txt", "dog in file\n")]);
+        let a = root.join("a.txt").display().to_string();
+
+        let (_, output) = run_grep(&["dog", &a], "dog in stdin\n");
+        assert_eq!(output, "dog in file\n");
+        let (_, output) = run_grep(&["dog"], "dog in stdin\n");
+        assert_eq!(output, "dog in stdin\n");
+        let (_, output) = run_grep(&["dog", "-"], "dog in stdin\n");
+        assert_eq!(output, "dog in stdin\n");
+        let (_, output) = run_grep(&["dog", &a, "-"], "dog in stdin\n");
+        assert_eq!(
+            output,
+            format!("{}:dog in file\n(standard input):dog in stdin\n", a)
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn count_lines_and_matches() {
+        let input = "a1 b2 c3\nnone\n";
+        let (selected, output) = run_grep(&["-c", "\\d"], input);
+        assert!(selected);
+        assert_eq!(output, "1\n");
+        let (_, output) = run_grep(&["--count-matches", "\\d"], input);
+        assert_eq!(output, "3\n");
+        let (selected, output) = run_grep(&["-c", "x"], input);
+        assert!(!selected);
+        assert_eq!(output, "0\n");
+    }
+
+    #[test]
+    fn multiline_anchors_in_null_data_records() {
+        let input = "first\nsecond\n\0other\n";
+        assert_eq!(run_grep(&["-z", "^second$"], input), (false, String::new()));
+        assert_eq!(
+            run_grep(&["-z", "--multiline", "^second$"], input),
+            (true, "first\nsecond\n\0".to_string())
+        );
+    }
+
+    #[test]
+    fn list_files_with_and_without_match() {
+        let root = create_tree("list", &[("a.txt", "cat\ndog\n"), ("b.txt", "bird\n")]);
+        let a = root.join("a.txt").display().to_string();
+        let b = root.join("b.txt").display().to_string();
+
+        assert_eq!(
+            run_grep(&["-l", "dog", &a, &b], ""),
+            (true, format!("{}\n", a))
+        );
+        assert_eq!(
+            run_grep(&["-L", "dog", &a, &b], ""),
+            (true, format!("{}\n", b))
+        );
+        assert_eq!(run_grep(&["-L", "dog", &a], ""), (false, String::new()));
+        assert_eq!(
+            run_grep(&["-l", "dog"], "hotdog\n"),
+            (true, "(standard input)\n".to_string())
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn print_only_matches() {
+        let input = "a1 b22 c333\nnone\nd4\n";
+        assert_eq!(
+            run_grep(&["-o", "\\d+"], input),
+            (true, "1\n22\n333\n4\n".to_string())
+        );
+        // the empty matches of \d* between the digits are not printed
+        assert_eq!(
+            run_grep(&["-o", "\\d*"], input),
+            (true, "1\n22\n333\n4\n".to_string())
+        );
+    }
+
+    #[test]
+    fn read_pattern_from_first_line_of_stdin() {
+        let input = "^\\d+$\n12\nab\n345\n";
+        assert_eq!(
+            run_grep(&["-E", "-"], input),
+            (true, "12\n345\n".to_string())
+        );
+        let options = Options::parse(["-".to_string()]).unwrap();
+        assert!(run(&options, Cursor::new(""), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn print_byte_offsets_of_lines_and_matches() {
+        let input = "a1 b22\nnone\nc333\n";
+        assert_eq!(
+            run_grep(&["-b", "\\d"], input),
+            (true, "0:a1 b22\n12:c333\n".to_string())
+        );
+        assert_eq!(
+            run_grep(&["-ob", "\\d+"], input),
+            (true, "1:1\n4:22\n13:333\n".to_string())
+        );
+        assert_eq!(
+            run_grep(&["-o", "-b", "\\d+"], input),
+            run_grep(&["-ob", "\\d+"], input)
+        );
+    }
+
+    #[test]
+    fn select_same_lines_with_any_buffer_size() {
+        let root = create_tree("buffers", &[("a.txt", "cat\ndog\nhotdog\nbird\n")]);
+        let a = root.join("a.txt").display().to_string();
+        let input = "dog\n".repeat(100) + "cat\n";
+
+        for args in [vec!["dog", "-"], vec!["-c", "dog", &a, "-"]] {
+            let default = run_grep(&args, &input);
+            let small = run_grep(&[&["--buffer-size=3"], args.as_slice()].concat(), &input);
+            assert_eq!(small, default);
+        }
+        assert!(Options::parse(["--buffer-size=0".to_string(), "a".to_string()]).is_err());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn invert_match_with_count_and_only_matching() {
+        let input = "cat\ndog\nbird\nhotdog\n";
+        assert_eq!(
+            run_grep(&["-v", "dog"], input),
+            (true, "cat\nbird\n".to_string())
+        );
+        assert_eq!(run_grep(&["-vc", "dog"], input), (true, "2\n".to_string()));
+        assert_eq!(run_grep(&["-vc", "\\w"], input), (false, "0\n".to_string()));
+        // the selected lines do not contain matches that could be printed
+        assert_eq!(run_grep(&["-vo", "dog"], input), (true, String::new()));
+        assert_eq!(run_grep(&["-v", "-o", "dog"], input), (true, String::new()));
+    }
+
+    #[test]
+    fn fixed_strings_with_literal_anchors() {
+        let input = "a$\na\n^b\nb\n";
+        assert_eq!(run_grep(&["-F", "a$"], input), (true, "a$\n".to_string()));
+        assert_eq!(run_grep(&["-F", "^b"], input), (true, "^b\n".to_string()));
+        assert_eq!(run_grep(&["a$"], input), (true, "a\n".to_string()));
+        assert_eq!(run_grep(&["^b"], input), (true, "b\n".to_string()));
+    }
+
+    #[test]
+    fn skip_lines_longer_than_max_line_length() {
+        let input = format!("short dog\n{}dog\nhotdog\n", "x".repeat(100));
+        let (_, output) = run_grep(&["--max-line-length=10", "dog"], &input);
+        assert_eq!(output, "short dog\nhotdog\n");
+        let (_, output) = run_grep(&["-c", "--max-line-length=10", "dog"], &input);
+        assert_eq!(output, "2\n");
+        let (_, output) = run_grep(&["-c", "dog"], &input);
+        assert_eq!(output, "3\n");
+    }
+
+    #[test]
+    fn fixed_strings_contrast_with_wildcards() {
+        let input = "a.b\naxb\na[b\n";
+        assert_eq!(run_grep(&["-F", "a.b"], input), (true, "a.b\n".to_string()));
+        assert_eq!(
+            run_grep(&["a.b"], input),
+            (true, "a.b\naxb\na[b\n".to_string())
+        );
+        // invalid regular expressions are fine as fixed strings
+        assert_eq!(run_grep(&["-F", "a[b"], input), (true, "a[b\n".to_string()));
+        let options = Options::parse(["a[b".to_string()]).unwrap();
+        assert!(run(&options, Cursor::new(input), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn select_whole_words() {
+        let input = "the cat sat\ncategory\nbobcat\n";
+        assert_eq!(
+            run_grep(&["-w", "cat"], input),
+            (true, "the cat sat\n".to_string())
+        );
+        assert_eq!(
+            run_grep(&["-w", "category|dog"], input),
+            (true, "category\n".to_string())
+        );
+        assert_eq!(
+            run_grep(&["-w", "cat"], "category\n"),
+            (false, String::new())
+        );
+    }
+
+    #[test]
+    fn quiet_stops_at_first_selected_line() {
+        let input = format!("{}dog\n{}", "cat\n".repeat(10_000), "dog\n".repeat(10));
+        assert_eq!(run_grep(&["-q", "dog"], &input), (true, String::new()));
+        assert_eq!(
+            run_grep(&["-q", "bird"], "cat\ndog"),
+            (false, String::new())
+        );
+        assert_eq!(run_grep(&["-q", "dog"], "cat\ndog"), (true, String::new()));
+
+        /// Reader failing on every read, it must never be reached after the selected line
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read after the selected line"))
+            }
+        }
+
+        let options = Options::parse(["-q".to_string(), "dog".to_string()]).unwrap();
+        let input = std::io::Read::chain(Cursor::new(input), Failing);
+        let mut output = Vec::new();
+        let selected = run(&options, input, &mut output);
+        assert!(matches!(selected, Ok(true)));
+        assert!(output.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recursive_skips_symlinks_and_binary_files() {
+        let root = create_tree("links", &[("a.txt", "dog\n"), ("sub/b.txt", "hotdog\n")]);
+        std::os::unix::fs::symlink(&root, root.join("sub/parent")).unwrap();
+        std::os::unix::fs::symlink(root.join("a.txt"), root.join("link.txt")).unwrap();
+        fs::write(root.join("binary.bin"), b"dog \xff\n").unwrap();
+        fs::write(root.join("mixed.bin"), b"dog\ndog\n\xff dog\ndog\n").unwrap();
+        let dir = root.display().to_string();
+        let name = |path: &str| root.join(path).display().to_string();
+
+        // files are only searched up to their first line that is not valid UTF-8
+        let (selected, output) = run_grep(&["-r", "dog", &dir], "");
+        assert!(selected);
+        assert_eq!(
+            output,
+            format!(
+                "{}:dog\n{}:dog\n{}:dog\n{}:hotdog\n",
+                name("a.txt"),
+                name("mixed.bin"),
+                name("mixed.bin"),
+                name("sub/b.txt")
+            )
+        );
+        let (_, output) = run_grep(&["-r", "-c", "dog", &dir], "");
+        assert_eq!(
+            output,
+            format!(
+                "{}:1\n{}:0\n{}:2\n{}:1\n",
+                name("a.txt"),
+                name("binary.bin"),
+                name("mixed.bin"),
+                name("sub/b.txt")
+            )
+        );
+        let (_, output) = run_grep(&["-r", "-l", "dog", &dir], "");
+        assert_eq!(
+            output,
+            format!(
+                "{}\n{}\n{}\n",
+                name("a.txt"),
+                name("mixed.bin"),
+                name("sub/b.txt")
+            )
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use std::io;
+use thiserror::Error;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Errors that can occur while compiling or matching a pattern
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The pattern is invalid, the position is the byte offset of the problem in the pattern
+    #[error("{message} at position {position}")]
+    Parse { message: String, position: usize },
+    /// A bracket group is not closed, the position is the byte offset of its opening bracket
+    #[error("Unbalanced bracket at position {position}")]
+    UnbalancedBracket { position: usize },
+    /// A group is not closed or a closing parenthesis has no group, the position is the byte
+    /// offset of the parenthesis without a counterpart
+    #[error("Unbalanced parenthesis at position {position}")]
+    UnbalancedParen { position: usize },
+    /// Matching was aborted because it needed more backtracking steps than allowed
+    #[error("Step limit exceeded")]
+    StepLimitExceeded,
+    /// Matching was aborted because the matches nested too deep for the stack, e.g. by
+    /// repeating a group very often
+    #[error("Depth limit exceeded")]
+    DepthLimitExceeded,
+    /// The offset is outside of the input or not at a character boundary
+    #[error("Invalid offset {0}")]
+    InvalidOffset(usize),
+    /// A replacement refers to a capture group the pattern does not have
+    #[error("Invalid group reference ${0}")]
+    InvalidGroupReference(usize),
+    /// Reading the input failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
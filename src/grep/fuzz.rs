@@ -0,0 +1,137 @@
+use super::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Linear congruential generator so every run checks the same patterns
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % bound as u64) as usize
+        }
+
+        fn pick(&mut self, choices: &[&'static str]) -> &'static str {
+            choices[self.next(choices.len())]
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Atom {
+        Character(char),
+        Wildcard,
+    }
+
+    /// Pattern of the simple subset: an optional ^, repeated atoms and an optional $
+    struct Reference {
+        start_anchor: bool,
+        items: Vec<(Atom, usize, Option<usize>)>,
+        end_anchor: bool,
+    }
+
+    impl Reference {
+        fn generate(random: &mut Random) -> (String, Reference) {
+            let mut pattern = String::new();
+            let start_anchor = random.next(4) == 0;
+            if start_anchor {
+                pattern.push('^');
+            }
+
+            let mut items = Vec::new();
+            for _ in 0..1 + random.next(4) {
+                let atom = random.pick(&["a", "b", "."]);
+                let quantifier = random.pick(&["", "", "?", "*", "+", "{2}", "{1,2}"]);
+                pattern.push_str(atom);
+                pattern.push_str(quantifier);
+
+                let atom = match atom {
+                    "." => Atom::Wildcard,
+                    c => Atom::Character(c.chars().next().unwrap()),
+                };
+                let (min, max) = match quantifier {
+                    "?" => (0, Some(1)),
+                    "*" => (0, None),
+                    "+" => (1, None),
+                    "{2}" => (2, Some(2)),
+                    "{1,2}" => (1, Some(2)),
+                    _ => (1, Some(1)),
+                };
+                items.push((atom, min, max));
+            }
+
+            let end_anchor = random.next(4) == 0;
+            if end_anchor {
+                pattern.push('$');
+            }
+            let reference = Reference {
+                start_anchor,
+                items,
+                end_anchor,
+            };
+            (pattern, reference)
+        }
+
+        /// Tracks the set of reachable positions instead of backtracking
+        fn is_match(&self, input: &[char]) -> bool {
+            let starts: Vec<usize> = if self.start_anchor {
+                vec![0]
+            } else {
+                (0..=input.len()).collect()
+            };
+
+            starts.into_iter().any(|start| {
+                let mut positions = vec![start];
+                for (atom, min, max) in &self.items {
+                    let mut reached = Vec::new();
+                    let mut current = positions;
+                    for count in 0..=max.unwrap_or(input.len()) {
+                        if count >= *min {
+                            reached.extend(&current);
+                        }
+                        current = current
+                            .into_iter()
+                            .filter(|position| match (atom, input.get(*position)) {
+                                (Atom::Character(c), Some(input)) => c == input,
+                                (Atom::Wildcard, Some(input)) => *input != '\n',
+                                (_, None) => false,
+                            })
+                            .map(|position| position + 1)
+                            .collect();
+                    }
+                    reached.sort();
+                    reached.dedup();
+                    positions = reached;
+                }
+                positions
+                    .iter()
+                    .any(|position| !self.end_anchor || *position == input.len())
+            })
+        }
+    }
+
+    #[test]
+    fn is_match_agrees_with_reference() {
+        let mut random = Random(0x5eed);
+        for _ in 0..2000 {
+            let (pattern, reference) = Reference::generate(&mut random);
+            let input: String = (0..random.next(7))
+                .map(|_| random.pick(&["a", "b", "c"]))
+                .collect();
+
+            let regex = Regex::new(&pattern).unwrap();
+            let expected = reference.is_match(&input.chars().collect::<Vec<_>>());
+            assert_eq!(
+                regex.is_match(&input),
+                expected,
+                "pattern {:?} input {:?}",
+                pattern,
+                input
+            );
+        }
+    }
+}
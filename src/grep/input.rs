@@ -0,0 +1,42 @@
+/// Input a pattern can be matched against, only single bytes are accessed so the input does
+/// not have to be contiguous, e.g. the chunks of a rope used by an editor
+pub trait Input {
+    /// Returns the number of bytes of the input
+    fn len(&self) -> usize;
+
+    /// Returns the byte at the index or None if the index is at or after the end
+    fn byte_at(&self, index: usize) -> Option<u8>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns if a match may start at the byte offset, e.g. not inside of a UTF-8 character
+    fn is_char_boundary(&self, index: usize) -> bool {
+        index <= self.len()
+    }
+}
+
+impl Input for str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn byte_at(&self, index: usize) -> Option<u8> {
+        self.as_bytes().get(index).copied()
+    }
+
+    fn is_char_boundary(&self, index: usize) -> bool {
+        str::is_char_boundary(self, index)
+    }
+}
+
+impl Input for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn byte_at(&self, index: usize) -> Option<u8> {
+        self.get(index).copied()
+    }
+}
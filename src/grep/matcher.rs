@@ -0,0 +1,476 @@
+use super::error::Error;
+use super::error::Result;
+use super::CharacterType;
+use super::Config;
+use super::Group;
+use super::Input;
+use super::MatchingType;
+use super::Pattern;
+use super::Repetition;
+
+/// Maximum number of backtracking steps a single match attempt may take
+pub(super) const STEP_LIMIT: usize = 1_000_000;
+/// Maximum number of bytes of stack a single match attempt may use, matches nest deeper with
+/// every matched item and repetitions of groups can not be matched without nesting, chosen to
+/// stay well within the 2 MiB stack of spawned threads
+pub(super) const STACK_LIMIT: usize = 1024 * 1024;
+
+/// Start and end of the whole match (index 0) and of every capture group
+pub(super) type Slots = Vec<Option<(usize, usize)>>;
+
+type Continuation<'c, 'a, I> = &'c mut dyn FnMut(&mut Matcher<'a, I>, usize) -> Result<bool>;
+
+pub(super) struct Matcher<'a, I: Input + ?Sized = [u8]> {
+    input: &'a I,
+    config: &'a Config,
+    steps: usize,
+    /// Stack address at the start of the current match attempt, see `STACK_LIMIT`
+    stack_start: usize,
+    captures: Slots,
+    /// Start of the reported match if it was reset by \K
+    kept_start: Option<usize>,
+    /// Start of the current match attempt
+    attempt_start: usize,
+    /// Start and position of the attempt that got the furthest and the description of the item
+    /// tried there, only tracked to explain failed matches as describing items is slow
+    furthest: Option<(usize, usize, String)>,
+    explain: bool,
+}
+
+impl<'a, I: Input + ?Sized> Matcher<'a, I> {
+    pub(super) fn new(input: &'a I, config: &'a Config) -> Matcher<'a, I> {
+        Matcher {
+            input,
+            config,
+            steps: 0,
+            stack_start: 0,
+            captures: Vec::new(),
+            kept_start: None,
+            attempt_start: 0,
+            furthest: None,
+            explain: false,
+        }
+    }
+
+    /// Creates a matcher that remembers the furthest item tried, see `furthest_item`
+    pub(super) fn explaining(input: &'a I, config: &'a Config) -> Matcher<'a, I> {
+        Matcher {
+            explain: true,
+            ..Matcher::new(input, config)
+        }
+    }
+
+    /// Returns the furthest position any match attempt reached and the item tried there,
+    /// which is the item the attempt failed at if no attempt matched
+    pub(super) fn furthest_item(&self) -> Option<(usize, &str)> {
+        let (_, position, item) = self.furthest.as_ref()?;
+        Some((*position, item))
+    }
+
+    /// Remembers the item if the current attempt got further than all others, earlier
+    /// attempts win ties
+    fn track_furthest(&mut self, position: usize, item: &MatchingType) {
+        let progress = position - self.attempt_start;
+        let further = match &self.furthest {
+            Some((start, end, _)) => progress > end - start,
+            None => true,
+        };
+        if further {
+            self.furthest = Some((self.attempt_start, position, item.describe()));
+        }
+    }
+
+    /// Tries to match the pattern at exactly `start` and returns the match and capture positions
+    pub(super) fn match_at(&mut self, pattern: &Pattern, start: usize) -> Result<Option<Slots>> {
+        self.match_items_at(&pattern.items, pattern.group_count, start)
+    }
+
+    pub(super) fn match_items_at(
+        &mut self,
+        items: &[MatchingType],
+        group_count: usize,
+        start: usize,
+    ) -> Result<Option<Slots>> {
+        self.steps = 0;
+        self.stack_start = stack_address();
+        self.captures = vec![None; group_count + 1];
+        self.kept_start = None;
+        self.attempt_start = start;
+
+        let longest = self.config.leftmost_longest;
+        let mut slots: Option<Slots> = None;
+        self.match_sequence(items, start, &mut |matcher, position| {
+            let shorter = slots
+                .as_ref()
+                .is_some_and(|slots| slots[0].is_some_and(|(_, end)| end >= position));
+            if !shorter {
+                let mut captures = matcher.captures.clone();
+                captures[0] = Some((matcher.kept_start.unwrap_or(start), position));
+                slots = Some(captures);
+            }
+            // the longest match keeps backtracking through all matches unless none can be longer
+            Ok(!longest || position == matcher.input.len())
+        })?;
+        Ok(slots)
+    }
+
+    /// Returns if the pattern matches the whole input and not only a part of it
+    pub(super) fn full_match(&mut self, pattern: &Pattern) -> Result<bool> {
+        self.match_between(pattern, 0, self.input.len())
+    }
+
+    /// Returns if the pattern has a match starting at `start` that ends exactly at `end`,
+    /// even if another match starting there would be preferred
+    pub(super) fn match_between(
+        &mut self,
+        pattern: &Pattern,
+        start: usize,
+        end: usize,
+    ) -> Result<bool> {
+        self.steps = 0;
+        self.stack_start = stack_address();
+        self.captures = vec![None; pattern.group_count + 1];
+        self.match_sequence(
+            &pattern.items,
+            start,
+            &mut |_, position| Ok(position == end),
+        )
+    }
+
+    /// Returns the end of the shortest match of the pattern starting at exactly `start`
+    pub(super) fn shortest_match_at(
+        &mut self,
+        pattern: &Pattern,
+        start: usize,
+    ) -> Result<Option<usize>> {
+        self.steps = 0;
+        self.stack_start = stack_address();
+        self.captures = vec![None; pattern.group_count + 1];
+
+        let mut shortest: Option<usize> = None;
+        // reject every match to backtrack through all of them
+        self.match_sequence(&pattern.items, start, &mut |_, position| {
+            shortest = Some(shortest.map_or(position, |end| end.min(position)));
+            Ok(position == start)
+        })?;
+        Ok(shortest)
+    }
+
+    fn step(&mut self) -> Result<()> {
+        self.steps += 1;
+        if self.steps > STEP_LIMIT {
+            return Err(Error::StepLimitExceeded);
+        }
+        Ok(())
+    }
+
+    fn match_sequence(
+        &mut self,
+        items: &[MatchingType],
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        self.step()?;
+        // fail before the nested matches overflow the stack
+        if self.stack_start.abs_diff(stack_address()) > STACK_LIMIT {
+            return Err(Error::DepthLimitExceeded);
+        }
+
+        let Some((item, rest)) = items.split_first() else {
+            return next(self, position);
+        };
+        if self.explain {
+            self.track_furthest(position, item);
+        }
+
+        match item {
+            MatchingType::Simple(c) => {
+                self.match_character_type(c, position, &mut |matcher, end| {
+                    matcher.match_sequence(rest, end, next)
+                })
+            }
+            MatchingType::Repeated(c, repetition) if c.is_single_character() => {
+                self.match_repetition(c, repetition, rest, position, next)
+            }
+            MatchingType::Repeated(c, repetition) if repetition.possessive => {
+                self.match_possessive_repetition(c, repetition, rest, position, next)
+            }
+            MatchingType::Repeated(c, repetition) => {
+                self.match_backtracking_repetition(c, repetition, 0, rest, position, next)
+            }
+            MatchingType::StartAnchor if self.is_line_start(position) => {
+                self.match_sequence(rest, position, next)
+            }
+            MatchingType::EndAnchor if self.is_line_end(position) => {
+                self.match_sequence(rest, position, next)
+            }
+            MatchingType::WordBoundary if self.is_word_boundary(position) => {
+                self.match_sequence(rest, position, next)
+            }
+            MatchingType::StartAnchor | MatchingType::EndAnchor | MatchingType::WordBoundary => {
+                Ok(false)
+            }
+            MatchingType::KeepOut => {
+                let previous = self.kept_start.replace(position);
+                if self.match_sequence(rest, position, next)? {
+                    return Ok(true);
+                }
+                self.kept_start = previous;
+                Ok(false)
+            }
+            MatchingType::Alternation(alternatives) => {
+                for alternative in alternatives {
+                    let matched =
+                        self.match_sequence(alternative, position, &mut |matcher, end| {
+                            matcher.match_sequence(rest, end, next)
+                        })?;
+                    if matched {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    fn match_character_type(
+        &mut self,
+        character: &CharacterType,
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        match character {
+            CharacterType::Group(group) => self.match_group(group, position, next),
+            CharacterType::Backreference(index) => {
+                match self.match_backreference(*index, position) {
+                    Some(end) => next(self, end),
+                    None => Ok(false),
+                }
+            }
+            _ => match self.match_character(character, position) {
+                Some(end) => next(self, end),
+                None => Ok(false),
+            },
+        }
+    }
+
+    /// Matches the group content and captures it for as long as the rest of the pattern matches
+    fn match_group(
+        &mut self,
+        group: &Group,
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        self.match_sequence(&group.items, position, &mut |matcher, end| {
+            let previous = matcher.captures[group.index];
+            matcher.captures[group.index] = Some((position, end));
+            if next(matcher, end)? {
+                return Ok(true);
+            }
+            matcher.captures[group.index] = previous;
+            Ok(false)
+        })
+    }
+
+    /// Matches the input captured by the group again, groups that did not match never match
+    fn match_backreference(&self, index: usize, position: usize) -> Option<usize> {
+        let (start, end) = self.captures[index]?;
+        let len = end - start;
+        let matched = position + len <= self.input.len()
+            && (0..len).all(|i| self.input.byte_at(start + i) == self.input.byte_at(position + i));
+        matched.then_some(position + len)
+    }
+
+    /// Greedily matches the character as often as possible and gives back one by one,
+    /// possessive repetitions only try the longest match and lazy ones start with the shortest
+    fn match_repetition(
+        &mut self,
+        character: &CharacterType,
+        repetition: &Repetition,
+        rest: &[MatchingType],
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        let max = repetition.max.unwrap_or(usize::MAX);
+        let mut ends = vec![position];
+        while ends.len() <= max {
+            match self.match_character(character, ends[ends.len() - 1]) {
+                Some(end) => ends.push(end),
+                None => break,
+            }
+        }
+
+        let longest = ends.len() - 1;
+        let shortest = if repetition.possessive {
+            longest.max(repetition.min)
+        } else {
+            repetition.min
+        };
+        let mut candidates = ends.split_off(shortest.min(ends.len()));
+        if !repetition.lazy {
+            candidates.reverse();
+        }
+        for end in candidates {
+            if self.match_sequence(rest, end, next)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Repeats a sub pattern greedily (or lazily) and backtracks into every single repetition
+    fn match_backtracking_repetition(
+        &mut self,
+        character: &CharacterType,
+        repetition: &Repetition,
+        count: usize,
+        rest: &[MatchingType],
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        self.step()?;
+
+        let can_stop = count >= repetition.min;
+        if repetition.lazy && can_stop && self.match_sequence(rest, position, next)? {
+            return Ok(true);
+        }
+
+        if count < repetition.max.unwrap_or(usize::MAX) {
+            let matched = self.match_character_type(character, position, &mut |matcher, end| {
+                // stop repeating sub patterns matching the empty input once the minimum is reached
+                if end == position && count >= repetition.min {
+                    return Ok(false);
+                }
+                matcher.match_backtracking_repetition(
+                    character,
+                    repetition,
+                    count + 1,
+                    rest,
+                    end,
+                    next,
+                )
+            })?;
+            if matched {
+                return Ok(true);
+            }
+        }
+
+        if can_stop && !repetition.lazy {
+            self.match_sequence(rest, position, next)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Repeats a sub pattern as often as possible without ever giving back a repetition
+    fn match_possessive_repetition(
+        &mut self,
+        character: &CharacterType,
+        repetition: &Repetition,
+        rest: &[MatchingType],
+        position: usize,
+        next: Continuation<'_, 'a, I>,
+    ) -> Result<bool> {
+        let captures = self.captures.clone();
+        let kept_start = self.kept_start;
+        let max = repetition.max.unwrap_or(usize::MAX);
+
+        let mut end = position;
+        let mut count = 0;
+        while count < max {
+            let mut repetition_end = None;
+            self.match_character_type(character, end, &mut |_, position| {
+                repetition_end = Some(position);
+                Ok(true)
+            })?;
+
+            match repetition_end {
+                Some(position) => {
+                    count += 1;
+                    if position == end {
+                        break;
+                    }
+                    end = position;
+                }
+                None => break,
+            }
+        }
+
+        if count >= repetition.min && self.match_sequence(rest, end, next)? {
+            return Ok(true);
+        }
+        self.captures = captures;
+        self.kept_start = kept_start;
+        Ok(false)
+    }
+
+    fn is_line_start(&self, position: usize) -> bool {
+        position == 0 || (self.config.multi_line && self.input.byte_at(position - 1) == Some(b'\n'))
+    }
+
+    fn is_line_end(&self, position: usize) -> bool {
+        position == self.input.len()
+            || (self.config.multi_line && self.input.byte_at(position) == Some(b'\n'))
+    }
+
+    /// Returns if a word starts at the position, i.e. a word character follows a boundary
+    pub(super) fn is_word_start(&self, position: usize) -> bool {
+        let after = decode(self.input, position).map(|(c, _)| c);
+        self.is_word_boundary(position) && after.is_some_and(|c| self.config.is_word_character(c))
+    }
+
+    fn is_word_boundary(&self, position: usize) -> bool {
+        let is_word = |c: Option<char>| c.is_some_and(|c| self.config.is_word_character(c));
+        let after = decode(self.input, position).map(|(c, _)| c);
+        is_word(self.previous_character(position)) != is_word(after)
+    }
+
+    fn previous_character(&self, position: usize) -> Option<char> {
+        let start = (position.saturating_sub(4)..position)
+            .find(|i| decode(self.input, *i).is_some_and(|(_, len)| i + len == position))?;
+        decode(self.input, start).map(|(c, _)| c)
+    }
+
+    fn match_character(&self, character: &CharacterType, position: usize) -> Option<usize> {
+        let (c, len) = decode(self.input, position)?;
+        if let CharacterType::Byte(byte) = character {
+            // byte literals only match input bytes that are not part of a valid character
+            let invalid = (c, len) == (char::REPLACEMENT_CHARACTER, 1);
+            return (invalid && self.input.byte_at(position) == Some(*byte))
+                .then_some(position + 1);
+        }
+        character.matches(c, self.config).then_some(position + len)
+    }
+}
+
+/// Returns the address of a local variable to measure how much stack is used between calls
+#[inline(never)]
+fn stack_address() -> usize {
+    let marker = 0u8;
+    core::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// Decodes the character at `position`, invalid UTF-8 bytes are returned as replacement character
+pub(super) fn decode<I: Input + ?Sized>(input: &I, position: usize) -> Option<(char, usize)> {
+    let first = input.byte_at(position)?;
+    if first.is_ascii() {
+        return Some((first as char, 1));
+    }
+
+    let mut buffer = [first; 4];
+    let mut len = 1;
+    while let Some(byte) = input.byte_at(position + len).filter(|_| len < buffer.len()) {
+        buffer[len] = byte;
+        len += 1;
+    }
+    let bytes = &buffer[..len];
+    let valid = match core::str::from_utf8(bytes) {
+        Ok(valid) => valid,
+        Err(error) => core::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default(),
+    };
+
+    match valid.chars().next() {
+        Some(c) => Some((c, c.len_utf8())),
+        None => Some((char::REPLACEMENT_CHARACTER, 1)),
+    }
+}
@@ -1,238 +1,696 @@
-use anyhow::bail;
-use anyhow::Result;
+use error::Result;
 
+pub use error::Error;
+pub use input::Input;
+pub use regex::CaptureMatches;
+pub use regex::Captures;
+pub use regex::Match;
+pub use regex::Matches;
+pub use regex::Regex;
+pub use regex::RegexBuilder;
+pub use regex::SplitItem;
+
+mod error;
+#[cfg(test)]
+mod fuzz;
+mod input;
+mod matcher;
+mod parser;
+mod reader;
+mod regex;
+#[cfg(test)]
 mod test;
 
 const CHARACTER_CLASS: u8 = b'\\';
 const CHARACTER_ALPHA: u8 = b'w';
 const CHARACTER_DIGIT: u8 = b'd';
+const CHARACTER_WHITESPACE: u8 = b's';
+const UNICODE_PROPERTY: u8 = b'p';
+const PROPERTY_START: u8 = b'{';
+const PROPERTY_END: u8 = b'}';
+const CODE_POINT: u8 = b'u';
+const CODE_POINT_START: u8 = b'{';
+const CODE_POINT_END: u8 = b'}';
+const WORD_BOUNDARY: u8 = b'b';
+const KEEP_OUT: u8 = b'K';
 const CHARACTER_WILDCARD: u8 = b'.';
 const START_ANCHOR: u8 = b'^';
 const END_ANCHOR: u8 = b'$';
 const ONE_OR_MORE: u8 = b'+';
 const ZERO_OR_ONE: u8 = b'?';
+const ZERO_OR_MORE: u8 = b'*';
+const POSSESSIVE: u8 = b'+';
+const LAZY: u8 = b'?';
+const BOUNDS_START: u8 = b'{';
+const BOUNDS_END: u8 = b'}';
+const BOUNDS_SEPARATOR: u8 = b',';
+/// Largest repetition bound allowed in patterns by default, e.g. a{1,10000}
+const REPETITION_LIMIT: usize = 10_000;
+const QUOTE_START: u8 = b'Q';
+const QUOTE_END: u8 = b'E';
+const ALTERNATION: u8 = b'|';
+const GROUP_START: u8 = b'(';
+const GROUP_END: u8 = b')';
+const GROUP_EXTENSION: u8 = b'?';
+const GROUP_NAME_PREFIX: u8 = b'P';
+const GROUP_NAME_START: u8 = b'<';
+const GROUP_NAME_END: u8 = b'>';
+const COMMENT_START: &[u8] = b"(?#";
+const EXTENDED_FLAG: &[u8] = b"(?x)";
+const EXTENDED_COMMENT: u8 = b'#';
+const BRACKET_START: u8 = b'[';
+const BRACKET_END: u8 = b']';
+const BRACKET_NEGATION: u8 = b'^';
+const BRACKET_RANGE: u8 = b'-';
+const META_CHARACTERS: &[u8] = b"\\.+*?()|[]{}^$";
+
+/// A parsed pattern together with the number of capture groups it contains
+#[derive(Clone, Debug)]
+struct Pattern {
+    items: Vec<MatchingType>,
+    group_count: usize,
+    /// Names of the named capture groups together with their index, e.g. (?<year>\d+)
+    names: Vec<(String, usize)>,
+}
 
-#[derive(Copy, Clone)]
+#[derive(Clone, Debug)]
 enum MatchingType {
     /// Simple types are matching exactly one time (no postfix operator)
     Simple(CharacterType),
-    /// Multiple types (+) are matching one or more times
-    Multiple(CharacterType),
-    /// Optional types (?) are matching zero or one time
-    Optional(CharacterType),
+    /// Repeated types are matching as often as their repetition allows
+    Repeated(CharacterType, Repetition),
+    /// Start anchor (^) matches only at the beginning of the input (or of a line in multi line mode)
+    StartAnchor,
+    /// End anchor ($) matches only at the end of the input (or of a line in multi line mode)
+    EndAnchor,
+    /// Word boundaries (\b) match between a word and a non word character
+    WordBoundary,
+    /// Keep out (\K) matches everywhere and lets the reported match start at its position
+    KeepOut,
+    /// Alternations (|) match if any alternative matches, earlier alternatives are preferred
+    /// and empty alternatives like in a| always match the empty input
+    Alternation(Vec<Vec<MatchingType>>),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
+struct Repetition {
+    /// Minimum number of matches, e.g. one for + and zero for ? and * (or n for {n,m})
+    min: usize,
+    /// Maximum number of matches, e.g. one for ? and unbounded for + and * (or m for {n,m})
+    max: Option<usize>,
+    /// Possessive repetitions (++, *+, ?+) never give back matched characters
+    possessive: bool,
+    /// Lazy repetitions (+?, *?, ??) match as few characters as possible
+    lazy: bool,
+}
+
+#[derive(Clone, Debug)]
 enum CharacterType {
     /// Character type is a character that matches exactly that character, e.g. 'a'
-    Character(u8),
+    Character(char),
+    /// Byte types match a raw byte of a pattern that is not valid UTF-8, e.g. 0xff
+    Byte(u8),
     /// Class types are a set of characters that can match the input
     Class(CharacterClass),
-    /// Character class to match any character (.)
+    /// Bracket groups match any (or with negation none) of the listed characters, e.g. [abc]
+    Bracket(BracketGroup),
+    /// Character class to match any character (.) except the line terminator
     Wildcard,
+    /// Groups match a sub pattern and capture the matched input, e.g. (abc)
+    Group(Group),
+    /// Backreferences match the same input as the capture group with the index, e.g. \1
+    Backreference(usize),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 enum CharacterClass {
     /// Character class that matches all ascii alpha numeric inputs
     Alpha,
     /// Character class that matches only digits
     Digit,
+    /// Character class that matches whitespace like spaces, tabs and newlines, \s
+    Whitespace,
+    /// Unicode property of letters, \p{L}
+    Letter,
+    /// Unicode property of numbers, \p{N}
+    Number,
+    /// Unicode property of punctuation, \p{P}
+    Punctuation,
 }
 
-enum MatchResult {
-    Positive(PositiveMatchResult),
-    Negative,
+/// Options that change how a pattern is compiled and matched, set by the `RegexBuilder`
+#[derive(Clone, Debug)]
+struct Config {
+    /// Wildcards (.) also match the line terminator
+    dot_matches_newline: bool,
+    /// Character separating records that wildcards do not match, a newline by default
+    line_terminator: u8,
+    /// Additional characters considered part of a word by \w and \b
+    word_characters: Vec<char>,
+    /// Largest repetition bound a pattern may use
+    repetition_limit: usize,
+    /// Basic patterns only treat the operators + ? | ( ) { } as such when they are escaped
+    basic: bool,
+    /// Replacements substitute references to missing groups with nothing instead of failing
+    lenient_group_references: bool,
+    /// Whitespace and # comments outside of bracket groups are ignored in the pattern
+    ignore_whitespace: bool,
+    /// Classes like \d and \s and properties like \p{L} match all Unicode characters of the class
+    /// instead of only ascii ones
+    unicode: bool,
+    /// Anchors (^ and $) also match after and before every newline inside of the input
+    multi_line: bool,
+    /// Matches report the longest match at the leftmost position like POSIX instead of the
+    /// first one found by backtracking
+    leftmost_longest: bool,
+    /// Patterns are literal strings in which no character has a special meaning, e.g. ^ and $
+    fixed_strings: bool,
+    /// Every pattern only matches whole words as if it was surrounded by word boundaries (\b)
+    whole_words: bool,
 }
 
-struct PositiveMatchResult {
-    pattern_chars: usize,
-    input_chars: usize,
+#[derive(Clone, Debug)]
+struct Group {
+    /// Index of the capture group, groups are counted by their opening parenthesis starting at 1
+    index: usize,
+    items: Vec<MatchingType>,
 }
 
-impl MatchingType {
-    fn get_type(pattern: &[u8]) -> Result<MatchingType> {
-        let character = CharacterType::get_type(pattern)?;
-
-        if pattern.len() > character.len() {
-            match pattern[character.len()] {
-                ONE_OR_MORE => Ok(MatchingType::Multiple(character)),
-                ZERO_OR_ONE => Ok(MatchingType::Optional(character)),
-                _ => Ok(MatchingType::Simple(character)),
+#[derive(Clone, Debug)]
+struct BracketGroup {
+    negated: bool,
+    characters: Vec<char>,
+    /// Inclusive character ranges, e.g. ('a', 'z') for [a-z]
+    ranges: Vec<(char, char)>,
+    /// Character classes listed in the group, e.g. \d for [\d.]
+    classes: Vec<CharacterClass>,
+}
+
+impl Pattern {
+    /// Returns the literal characters every match has to start with, e.g. "http" for http\w*
+    fn literal_prefix(&self) -> Vec<u8> {
+        let mut prefix = String::new();
+        for item in &self.items {
+            match item {
+                MatchingType::Simple(CharacterType::Character(c)) => prefix.push(*c),
+                _ => break,
             }
-        } else {
-            Ok(MatchingType::Simple(character))
         }
+        prefix.into_bytes()
     }
 
-    fn matches(&self, input: &[u8]) -> MatchResult {
-        match self {
-            MatchingType::Simple(c) => {
-                if input.is_empty() {
-                    MatchResult::Negative
-                } else {
-                    c.matches(input[0])
+    /// Returns the set of bytes every match has to start with, None if a match may start
+    /// with any byte or be empty
+    fn first_bytes(&self, config: &Config) -> Option<[bool; 256]> {
+        let mut bytes = [false; 256];
+        let nullable = add_first_bytes(&self.items, config, &mut bytes)?;
+        (!nullable).then_some(bytes)
+    }
+
+    /// Returns if the items can match without consuming input, anchors and boundaries are
+    /// assumed to match and `groups` are the groups currently checked
+    fn is_nullable(&self, items: &[MatchingType], groups: &mut Vec<usize>) -> bool {
+        items.iter().all(|item| match item {
+            MatchingType::Simple(c) => self.is_character_nullable(c, groups),
+            MatchingType::Repeated(c, repetition) => {
+                repetition.min == 0 || self.is_character_nullable(c, groups)
+            }
+            MatchingType::StartAnchor
+            | MatchingType::EndAnchor
+            | MatchingType::WordBoundary
+            | MatchingType::KeepOut => true,
+            MatchingType::Alternation(alternatives) => alternatives
+                .iter()
+                .any(|alternative| self.is_nullable(alternative, groups)),
+        })
+    }
+
+    fn is_character_nullable(&self, character: &CharacterType, groups: &mut Vec<usize>) -> bool {
+        match character {
+            CharacterType::Group(group) => self.is_group_nullable(group, groups),
+            // a backreference inside of its own group only repeats an earlier repetition
+            CharacterType::Backreference(index) if groups.contains(index) => false,
+            CharacterType::Backreference(index) => find_group(&self.items, *index)
+                .is_some_and(|group| self.is_group_nullable(group, groups)),
+            _ => false,
+        }
+    }
+
+    fn is_group_nullable(&self, group: &Group, groups: &mut Vec<usize>) -> bool {
+        groups.push(group.index);
+        let nullable = self.is_nullable(&group.items, groups);
+        groups.pop();
+        nullable
+    }
+
+    /// Returns the maximum number of bytes a match can consume, None if it is unbounded
+    fn max_len(&self) -> Option<usize> {
+        self.items_max_len(&self.items, &mut Vec::new())
+    }
+
+    /// `groups` are the groups currently measured, backreferences to them are unbounded
+    fn items_max_len(&self, items: &[MatchingType], groups: &mut Vec<usize>) -> Option<usize> {
+        items.iter().try_fold(0, |len: usize, item| {
+            let item_len = match item {
+                MatchingType::Simple(c) => self.character_max_len(c, groups)?,
+                MatchingType::Repeated(c, repetition) => {
+                    let character_len = self.character_max_len(c, groups)?;
+                    match repetition.max {
+                        _ if character_len == 0 => 0,
+                        Some(max) => character_len.checked_mul(max)?,
+                        None => return None,
+                    }
                 }
+                MatchingType::Alternation(alternatives) => {
+                    alternatives.iter().try_fold(0, |longest, alternative| {
+                        Some(longest.max(self.items_max_len(alternative, groups)?))
+                    })?
+                }
+                MatchingType::StartAnchor
+                | MatchingType::EndAnchor
+                | MatchingType::WordBoundary
+                | MatchingType::KeepOut => 0,
+            };
+            len.checked_add(item_len)
+        })
+    }
+
+    fn character_max_len(
+        &self,
+        character: &CharacterType,
+        groups: &mut Vec<usize>,
+    ) -> Option<usize> {
+        match character {
+            CharacterType::Character(c) => Some(c.len_utf8()),
+            CharacterType::Byte(_) => Some(1),
+            // any character may be matched which takes up to four bytes in UTF-8
+            CharacterType::Class(_) | CharacterType::Bracket(_) | CharacterType::Wildcard => {
+                Some(4)
+            }
+            CharacterType::Group(group) => self.group_max_len(group, groups),
+            CharacterType::Backreference(index) if groups.contains(index) => None,
+            CharacterType::Backreference(index) => {
+                self.group_max_len(find_group(&self.items, *index)?, groups)
             }
-            MatchingType::Multiple(c) => {
-                let matches = c.match_count(input);
-                MatchResult::new(matches > 0, c.len() + 1, matches)
+        }
+    }
+
+    fn group_max_len(&self, group: &Group, groups: &mut Vec<usize>) -> Option<usize> {
+        groups.push(group.index);
+        let len = self.items_max_len(&group.items, groups);
+        groups.pop();
+        len
+    }
+}
+
+/// Adds the bytes a match of the items can start with and returns if the items can match
+/// the empty input, None if a match can start with any byte
+fn add_first_bytes(
+    items: &[MatchingType],
+    config: &Config,
+    bytes: &mut [bool; 256],
+) -> Option<bool> {
+    for item in items {
+        let nullable = match item {
+            MatchingType::Simple(c) => c.add_first_bytes(config, bytes)?,
+            MatchingType::Repeated(c, repetition) => {
+                c.add_first_bytes(config, bytes)? || repetition.min == 0
             }
-            MatchingType::Optional(c) => {
-                let matches = c.match_count(input);
-                MatchResult::new(matches < 2, c.len() + 1, matches)
+            MatchingType::StartAnchor
+            | MatchingType::EndAnchor
+            | MatchingType::WordBoundary
+            | MatchingType::KeepOut => true,
+            MatchingType::Alternation(alternatives) => {
+                let mut nullable = false;
+                for alternative in alternatives {
+                    nullable |= add_first_bytes(alternative, config, bytes)?;
+                }
+                nullable
             }
+        };
+        if !nullable {
+            return Some(false);
         }
     }
+    Some(true)
 }
 
-impl CharacterType {
-    fn get_type(pattern: &[u8]) -> Result<CharacterType> {
-        match pattern[0] {
-            CHARACTER_CLASS => CharacterClass::get_type(pattern[1]),
-            CHARACTER_WILDCARD => Ok(CharacterType::Wildcard),
-            _ => Ok(CharacterType::Character(pattern[0])),
+/// Returns the group with the index from anywhere inside of the items
+fn find_group(items: &[MatchingType], index: usize) -> Option<&Group> {
+    items.iter().find_map(|item| match item {
+        MatchingType::Simple(CharacterType::Group(group))
+        | MatchingType::Repeated(CharacterType::Group(group), _) => {
+            if group.index == index {
+                Some(group)
+            } else {
+                find_group(&group.items, index)
+            }
+        }
+        MatchingType::Alternation(alternatives) => alternatives
+            .iter()
+            .find_map(|alternative| find_group(alternative, index)),
+        _ => None,
+    })
+}
+
+impl MatchingType {
+    /// Describes the item for explanations of failed matches, e.g. "literal 'a'"
+    fn describe(&self) -> String {
+        match self {
+            MatchingType::Simple(c) => c.describe(),
+            MatchingType::Repeated(c, _) => format!("repetition of {}", c.describe()),
+            MatchingType::StartAnchor => "start anchor".to_string(),
+            MatchingType::EndAnchor => "end anchor".to_string(),
+            MatchingType::WordBoundary => "word boundary".to_string(),
+            MatchingType::KeepOut => "keep out".to_string(),
+            MatchingType::Alternation(_) => "alternation".to_string(),
         }
     }
+}
 
-    fn matches(&self, input: u8) -> MatchResult {
+impl CharacterType {
+    fn describe(&self) -> String {
         match self {
-            CharacterType::Character(c) => MatchResult::new(&input == c, 1, 1),
-            CharacterType::Class(class) => class.matches(input),
-            CharacterType::Wildcard => MatchResult::new(true, 1, 1),
+            CharacterType::Character(c) => format!("literal '{}'", c),
+            CharacterType::Byte(byte) => format!("byte 0x{:02x}", byte),
+            CharacterType::Class(_) => "character class".to_string(),
+            CharacterType::Bracket(_) => "bracket group".to_string(),
+            CharacterType::Wildcard => "wildcard".to_string(),
+            CharacterType::Group(group) => format!("group {}", group.index),
+            CharacterType::Backreference(index) => format!("backreference \\{}", index),
         }
     }
 
-    fn len(&self) -> usize {
+    fn matches(&self, input: char, config: &Config) -> bool {
         match self {
-            CharacterType::Character(_) => 1,
-            CharacterType::Class(_) => 2,
-            CharacterType::Wildcard => 1,
+            CharacterType::Character(c) => input == *c,
+            CharacterType::Class(class) => class.matches(input, config),
+            CharacterType::Bracket(group) => group.matches(input, config),
+            CharacterType::Wildcard => {
+                config.dot_matches_newline || input != config.line_terminator as char
+            }
+            // raw bytes, groups and backreferences are not matched by a single character
+            // and are handled by the matcher
+            CharacterType::Byte(_) | CharacterType::Group(_) | CharacterType::Backreference(_) => {
+                false
+            }
         }
     }
 
-    fn match_count(&self, input: &[u8]) -> usize {
-        input
-            .iter()
-            .take_while(|i| self.matches(**i).is_matching())
-            .count()
+    /// Adds the bytes a match of the character type can start with, see `add_first_bytes`
+    fn add_first_bytes(&self, config: &Config, bytes: &mut [bool; 256]) -> Option<bool> {
+        match self {
+            CharacterType::Character(c) => {
+                let mut buffer = [0; 4];
+                bytes[c.encode_utf8(&mut buffer).as_bytes()[0] as usize] = true;
+            }
+            CharacterType::Byte(byte) => bytes[*byte as usize] = true,
+            CharacterType::Group(group) => return add_first_bytes(&group.items, config, bytes),
+            // the captured input is not known before matching
+            CharacterType::Backreference(_) => return None,
+            _ => {
+                for (byte, first) in bytes[..128].iter_mut().enumerate() {
+                    *first |= self.matches(byte as u8 as char, config);
+                }
+                // non ascii characters and invalid bytes of byte inputs start with any of these
+                if self.may_match_non_ascii(config) {
+                    bytes[128..].fill(true);
+                }
+            }
+        }
+        Some(false)
     }
-}
 
-impl CharacterClass {
-    fn get_type(pattern: u8) -> Result<CharacterType> {
-        match pattern {
-            CHARACTER_ALPHA => Ok(CharacterType::Class(CharacterClass::Alpha)),
-            CHARACTER_DIGIT => Ok(CharacterType::Class(CharacterClass::Digit)),
-            _ => bail!("Unhandled pattern: \\{}", pattern),
+    fn may_match_non_ascii(&self, config: &Config) -> bool {
+        match self {
+            CharacterType::Class(class) => class.may_match_non_ascii(config),
+            CharacterType::Bracket(group) => {
+                group.negated
+                    || group.characters.iter().any(|c| !c.is_ascii())
+                    || group.ranges.iter().any(|(_, end)| !end.is_ascii())
+                    || group
+                        .classes
+                        .iter()
+                        .any(|class| class.may_match_non_ascii(config))
+            }
+            _ => true,
         }
     }
 
-    fn matches(&self, input: u8) -> MatchResult {
-        let result = match self {
-            CharacterClass::Alpha => input.is_ascii_alphanumeric(),
-            CharacterClass::Digit => input.is_ascii_digit(),
-        };
-        MatchResult::new(result, 2, 1)
+    fn is_single_character(&self) -> bool {
+        !matches!(
+            self,
+            CharacterType::Group(_) | CharacterType::Backreference(_)
+        )
     }
 }
 
-impl MatchResult {
-    fn new(result: bool, pattern_chars: usize, input_chars: usize) -> MatchResult {
-        if result {
-            MatchResult::ok(pattern_chars, input_chars)
-        } else {
-            MatchResult::Negative
+impl CharacterClass {
+    fn may_match_non_ascii(&self, config: &Config) -> bool {
+        match self {
+            CharacterClass::Alpha => config.word_characters.iter().any(|c| !c.is_ascii()),
+            _ => config.unicode,
         }
     }
 
-    fn ok(pattern_chars: usize, input_chars: usize) -> MatchResult {
-        MatchResult::Positive(PositiveMatchResult {
-            pattern_chars,
-            input_chars,
-        })
-    }
-
-    fn is_matching(&self) -> bool {
+    fn matches(&self, input: char, config: &Config) -> bool {
         match self {
-            MatchResult::Positive(_) => true,
-            MatchResult::Negative => false,
+            CharacterClass::Alpha => config.is_word_character(input),
+            CharacterClass::Digit if config.unicode => is_decimal_digit(input),
+            CharacterClass::Digit => input.is_ascii_digit(),
+            CharacterClass::Whitespace if config.unicode => input.is_whitespace(),
+            CharacterClass::Whitespace => input.is_ascii_whitespace(),
+            CharacterClass::Letter if config.unicode => input.is_alphabetic(),
+            CharacterClass::Letter => input.is_ascii_alphabetic(),
+            CharacterClass::Number if config.unicode => input.is_numeric(),
+            CharacterClass::Number => input.is_ascii_digit(),
+            CharacterClass::Punctuation if config.unicode => is_punctuation(input),
+            CharacterClass::Punctuation => input.is_ascii_punctuation(),
         }
     }
 }
 
-fn match_match_group(input_line: &str, pattern: &str) -> Result<bool> {
-    let is_negative = pattern.chars().next().expect("no group speciefied") == '^';
-    let skip_chars = if is_negative { 1 } else { 0 };
-    let group = pattern.chars().skip(skip_chars);
-    Ok(group
-        .into_iter()
-        .any(|c| input_line.contains(c) != is_negative))
+/// Zeros of the runs of ten Unicode decimal digits (category Nd) as of Unicode 14
+const DECIMAL_DIGIT_ZEROS: &[char] = &[
+    '\u{30}',
+    '\u{660}',
+    '\u{6f0}',
+    '\u{7c0}',
+    '\u{966}',
+    '\u{9e6}',
+    '\u{a66}',
+    '\u{ae6}',
+    '\u{b66}',
+    '\u{be6}',
+    '\u{c66}',
+    '\u{ce6}',
+    '\u{d66}',
+    '\u{de6}',
+    '\u{e50}',
+    '\u{ed0}',
+    '\u{f20}',
+    '\u{1040}',
+    '\u{1090}',
+    '\u{17e0}',
+    '\u{1810}',
+    '\u{1946}',
+    '\u{19d0}',
+    '\u{1a80}',
+    '\u{1a90}',
+    '\u{1b50}',
+    '\u{1bb0}',
+    '\u{1c40}',
+    '\u{1c50}',
+    '\u{a620}',
+    '\u{a8d0}',
+    '\u{a900}',
+    '\u{a9d0}',
+    '\u{a9f0}',
+    '\u{aa50}',
+    '\u{abf0}',
+    '\u{ff10}',
+    '\u{104a0}',
+    '\u{10d30}',
+    '\u{11066}',
+    '\u{110f0}',
+    '\u{11136}',
+    '\u{111d0}',
+    '\u{112f0}',
+    '\u{11450}',
+    '\u{114d0}',
+    '\u{11650}',
+    '\u{116c0}',
+    '\u{11730}',
+    '\u{118e0}',
+    '\u{11950}',
+    '\u{11c50}',
+    '\u{11d50}',
+    '\u{11da0}',
+    '\u{16a60}',
+    '\u{16ac0}',
+    '\u{16b50}',
+    '\u{1d7ce}',
+    '\u{1d7d8}',
+    '\u{1d7e2}',
+    '\u{1d7ec}',
+    '\u{1d7f6}',
+    '\u{1e140}',
+    '\u{1e2f0}',
+    '\u{1e950}',
+    '\u{1fbf0}',
+];
+
+/// Returns if the character is a Unicode decimal digit, unlike `char::is_numeric` which also
+/// accepts other numbers like ½, ² or Ⅻ
+fn is_decimal_digit(input: char) -> bool {
+    DECIMAL_DIGIT_ZEROS
+        .iter()
+        .any(|zero| (input as u32).wrapping_sub(*zero as u32) < 10)
 }
 
-fn match_characters(input_line: &str, pattern: &str) -> Result<bool> {
-    let mut pattern_len = pattern.len();
-    let has_start_anchor = pattern.as_bytes()[0] == START_ANCHOR;
-    let has_end_anchor = pattern.as_bytes()[pattern_len - 1] == END_ANCHOR;
+/// Coarse check for the Unicode punctuation category, the standard library has no lookup
+/// for general categories so only the common punctuation blocks are covered
+fn is_punctuation(input: char) -> bool {
+    input.is_ascii_punctuation()
+        || matches!(
+            input,
+            '\u{a1}' | '\u{a7}' | '\u{ab}' | '\u{b6}' | '\u{b7}' | '\u{bb}' | '\u{bf}'
+                | '\u{2010}'..='\u{2027}'
+                | '\u{2030}'..='\u{205e}'
+                | '\u{3001}'..='\u{3003}'
+                | '\u{3008}'..='\u{3011}'
+        )
+}
 
-    if has_start_anchor {
-        pattern_len -= 1;
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            dot_matches_newline: false,
+            line_terminator: b'\n',
+            word_characters: Vec::new(),
+            repetition_limit: REPETITION_LIMIT,
+            basic: false,
+            lenient_group_references: false,
+            ignore_whitespace: false,
+            unicode: false,
+            multi_line: false,
+            leftmost_longest: false,
+            fixed_strings: false,
+            whole_words: false,
+        }
     }
-    if has_end_anchor {
-        pattern_len -= 1;
+}
+
+impl Config {
+    fn is_word_character(&self, input: char) -> bool {
+        input.is_ascii_alphanumeric() || self.word_characters.contains(&input)
     }
+}
 
-    if has_start_anchor {
-        match_characters_exact(input_line, &pattern[1..])
-    } else if has_end_anchor {
-        match_characters_exact(
-            &input_line[(input_line.len() - pattern_len)..],
-            &pattern[..pattern.len() - 1],
-        )
-    } else {
-        match_characters_iterate(input_line, pattern)
+impl BracketGroup {
+    fn matches(&self, input: char, config: &Config) -> bool {
+        let listed = self.characters.contains(&input)
+            || self
+                .classes
+                .iter()
+                .any(|class| class.matches(input, config))
+            || self
+                .ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&input));
+        listed != self.negated
     }
 }
 
-fn match_characters_iterate(input_line: &str, pattern: &str) -> Result<bool> {
-    for (i, _) in input_line.char_indices() {
-        if match_characters_exact(&input_line[i..], pattern)? {
-            return Ok(true);
+/// Returns the pattern features the engine supports, kept up to date as features are added
+pub fn supported_features() -> Vec<&'static str> {
+    vec![
+        "literals",
+        "wildcard",
+        "anchors",
+        "word boundaries",
+        "character classes",
+        "bracket groups",
+        "bracket ranges",
+        "quantifiers",
+        "bounded quantifiers",
+        "possessive quantifiers",
+        "lazy quantifiers",
+        "groups",
+        "named groups",
+        "alternation",
+        "backreferences",
+        "quoting",
+        "comments",
+        "extended patterns",
+        "keep out",
+        "basic syntax",
+        "byte patterns",
+        "unicode digits",
+        "multi line anchors",
+        "unicode properties",
+        "leftmost longest",
+        "code point escapes",
+    ]
+}
+
+/// Escapes all meta characters so the literal can be embedded into a pattern, whitespace and #
+/// are escaped as well to keep their meaning in patterns that ignore whitespace
+pub fn escape(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        let extended = c == EXTENDED_COMMENT as char || c.is_ascii_whitespace();
+        if extended || (c.is_ascii() && META_CHARACTERS.contains(&(c as u8))) {
+            escaped.push(CHARACTER_CLASS as char);
         }
+        escaped.push(c);
     }
-    Ok(false)
+    escaped
 }
 
-fn match_characters_exact(input_line: &str, pattern: &str) -> Result<bool> {
-    let mut input_index = 0;
-    let mut pattern_index = 0;
-    let input = input_line.as_bytes();
-    let pattern = pattern.as_bytes();
+pub fn match_pattern(input_line: &str, pattern: &str) -> Result<bool> {
+    let regex = Regex::new(pattern)?;
+    Ok(regex.try_find(input_line)?.is_some())
+}
 
-    while pattern_index < pattern.len() {
-        let current_pattern = &pattern[pattern_index..];
-        let current_input = &input[input_index..];
+/// Returns if the pattern matches the whole input line instead of only a part of it
+pub fn full_match(input_line: &str, pattern: &str) -> Result<bool> {
+    let regex = Regex::new(pattern)?;
+    regex.try_full_match(input_line)
+}
 
-        let char_type = MatchingType::get_type(current_pattern)?;
-        let result = char_type.matches(current_input);
+/// Matches a pattern against an input that both may contain bytes which are not valid UTF-8
+pub fn match_bytes(input: &[u8], pattern: &[u8]) -> Result<bool> {
+    let regex = Regex::from_bytes(pattern)?;
+    regex.try_is_match_bytes(input)
+}
 
-        match result {
-            MatchResult::Positive(result) => {
-                pattern_index += result.pattern_chars;
-                input_index += result.input_chars;
-            }
-            MatchResult::Negative => return Ok(false),
-        }
-    }
+/// Returns if the input contains an integer, i.e. a run of digits, between `lo` and `hi`
+/// inclusive, which is awkward to express as pattern
+pub fn match_number_range(input: &str, lo: u64, hi: u64) -> bool {
+    input
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| !digits.is_empty())
+        // digit runs too large for an u64 are above every range
+        .filter_map(|digits| digits.parse::<u64>().ok())
+        .any(|number| (lo..=hi).contains(&number))
+}
 
-    Ok(true)
+/// Compiles the patterns into one regex matching if any of them matches, each pattern
+/// keeps its own alternations and backreferences
+pub fn compile_any(patterns: &[&str]) -> Result<Regex> {
+    RegexBuilder::any(patterns).build()
 }
 
-pub fn match_pattern(input_line: &str, pattern: &str) -> Result<bool> {
-    if pattern.starts_with('[') && pattern.ends_with(']') {
-        let count = pattern.len();
-        match_match_group(input_line, &pattern[1..count - 2])
-    } else {
-        match_characters(input_line, pattern)
-    }
+/// Returns the captured text of every capture group for each match of the pattern
+pub fn capture_table(input: &str, pattern: &str) -> Result<Vec<Vec<Option<String>>>> {
+    let regex = Regex::new(pattern)?;
+    Ok(regex
+        .captures_iter(input)
+        .map(|captures| {
+            (1..captures.len())
+                .map(|i| captures.get(i).map(|m| m.as_str().to_string()))
+                .collect()
+        })
+        .collect())
 }
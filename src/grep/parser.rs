@@ -0,0 +1,586 @@
+use super::error::Error;
+use super::error::Result;
+use super::matcher::decode;
+use super::BracketGroup;
+use super::CharacterClass;
+use super::CharacterType;
+use super::Config;
+use super::Group;
+use super::MatchingType;
+use super::Pattern;
+use super::Repetition;
+use super::ALTERNATION;
+use super::BOUNDS_END;
+use super::BOUNDS_SEPARATOR;
+use super::BOUNDS_START;
+use super::BRACKET_END;
+use super::BRACKET_NEGATION;
+use super::BRACKET_RANGE;
+use super::BRACKET_START;
+use super::CHARACTER_ALPHA;
+use super::CHARACTER_CLASS;
+use super::CHARACTER_DIGIT;
+use super::CHARACTER_WHITESPACE;
+use super::CHARACTER_WILDCARD;
+use super::CODE_POINT;
+use super::CODE_POINT_END;
+use super::CODE_POINT_START;
+use super::COMMENT_START;
+use super::END_ANCHOR;
+use super::EXTENDED_COMMENT;
+use super::EXTENDED_FLAG;
+use super::GROUP_END;
+use super::GROUP_EXTENSION;
+use super::GROUP_NAME_END;
+use super::GROUP_NAME_PREFIX;
+use super::GROUP_NAME_START;
+use super::GROUP_START;
+use super::KEEP_OUT;
+use super::LAZY;
+use super::ONE_OR_MORE;
+use super::POSSESSIVE;
+use super::PROPERTY_END;
+use super::PROPERTY_START;
+use super::QUOTE_END;
+use super::QUOTE_START;
+use super::START_ANCHOR;
+use super::UNICODE_PROPERTY;
+use super::WORD_BOUNDARY;
+use super::ZERO_OR_MORE;
+use super::ZERO_OR_ONE;
+
+pub(super) struct Parser<'a> {
+    pattern: &'a [u8],
+    position: usize,
+    group_count: usize,
+    /// Number of groups in the patterns parsed before this one, see `parse_any`
+    group_offset: usize,
+    /// Names of the named groups parsed so far in this and the previous patterns
+    names: Vec<(String, usize)>,
+    /// Whitespace and # comments are ignored, enabled by the config or (?x)
+    extended: bool,
+    config: &'a Config,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses the patterns as alternatives of one pattern, groups are numbered continuously
+    /// over all patterns while backreferences refer to the groups of their own pattern
+    pub(super) fn parse_any(patterns: &[&'a [u8]], config: &'a Config) -> Result<Pattern> {
+        let mut alternatives = Vec::new();
+        let mut group_count = 0;
+        let mut names = Vec::new();
+        for pattern in patterns {
+            let mut parser = Parser {
+                pattern,
+                position: 0,
+                group_count,
+                group_offset: group_count,
+                names,
+                extended: config.ignore_whitespace,
+                config,
+            };
+
+            let mut items = if config.fixed_strings {
+                parser.parse_literal()?
+            } else {
+                parser.parse_alternation()?
+            };
+            if !parser.is_empty() {
+                let position = parser.position;
+                return Err(Error::UnbalancedParen { position });
+            }
+            if config.whole_words {
+                // inside of each alternative to keep a top level alternation the only item
+                match items.as_mut_slice() {
+                    [MatchingType::Alternation(alternatives)] => {
+                        alternatives.iter_mut().for_each(add_word_boundaries)
+                    }
+                    _ => add_word_boundaries(&mut items),
+                }
+            }
+            alternatives.push(items);
+            group_count = parser.group_count;
+            names = parser.names;
+        }
+
+        let items = if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            vec![MatchingType::Alternation(alternatives)]
+        };
+        Ok(Pattern {
+            items,
+            group_count,
+            names,
+        })
+    }
+
+    /// Parses the whole pattern as literal characters, anchors and operators included
+    fn parse_literal(&mut self) -> Result<Vec<MatchingType>> {
+        let mut items = Vec::new();
+        while !self.is_empty() {
+            items.push(MatchingType::Simple(self.next_literal()?));
+        }
+        Ok(items)
+    }
+
+    /// Parses all alternatives until the end of the pattern or the end of the current group
+    fn parse_alternation(&mut self) -> Result<Vec<MatchingType>> {
+        let mut alternatives = vec![self.parse_sequence()?];
+        while self.consume_operator(ALTERNATION) {
+            alternatives.push(self.parse_sequence()?);
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(vec![MatchingType::Alternation(alternatives)])
+        }
+    }
+
+    fn parse_sequence(&mut self) -> Result<Vec<MatchingType>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_ignored();
+            if self.is_empty() || self.peek_operator(GROUP_END) || self.peek_operator(ALTERNATION) {
+                break;
+            }
+
+            if self.consume_escape(QUOTE_START) {
+                self.parse_quoted(&mut items)?;
+            } else if self.pattern[self.position..].starts_with(COMMENT_START) && !self.config.basic
+            {
+                self.skip_comment()?;
+            } else if self.pattern[self.position..].starts_with(EXTENDED_FLAG) && !self.config.basic
+            {
+                self.position += EXTENDED_FLAG.len();
+                self.extended = true;
+            } else {
+                items.push(self.parse_matching_type()?);
+            }
+        }
+        Ok(items)
+    }
+
+    /// Skips whitespace and comments from # to the end of the line in extended patterns
+    fn skip_ignored(&mut self) {
+        while self.extended {
+            match self.peek() {
+                Some(b) if b.is_ascii_whitespace() => self.position += 1,
+                Some(EXTENDED_COMMENT) => {
+                    while !self.is_empty() && !self.consume(b'\n') {
+                        self.position += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Skips a comment (?#...) up to the next closing parenthesis
+    fn skip_comment(&mut self) -> Result<()> {
+        let start = self.position;
+        self.position += COMMENT_START.len();
+        while !self.consume(GROUP_END) {
+            if self.is_empty() {
+                return self.error(start, "Missing end of comment");
+            }
+            self.position += 1;
+        }
+        Ok(())
+    }
+
+    fn error<T>(&self, position: usize, message: &str) -> Result<T> {
+        Err(Error::Parse {
+            message: message.to_string(),
+            position,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.pattern.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.pattern.get(self.position).copied()
+    }
+
+    fn consume(&mut self, expected: u8) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn consume_escape(&mut self, expected: u8) -> bool {
+        let found = self.peek() == Some(CHARACTER_CLASS)
+            && self.pattern.get(self.position + 1) == Some(&expected);
+        if found {
+            self.position += 2;
+        }
+        found
+    }
+
+    /// Operators like + and ( have to be escaped in basic patterns and must not be in extended ones
+    fn peek_operator(&self, operator: u8) -> bool {
+        if self.config.basic {
+            self.peek() == Some(CHARACTER_CLASS)
+                && self.pattern.get(self.position + 1) == Some(&operator)
+        } else {
+            self.peek() == Some(operator)
+        }
+    }
+
+    fn consume_operator(&mut self, operator: u8) -> bool {
+        if self.config.basic {
+            self.consume_escape(operator)
+        } else {
+            self.consume(operator)
+        }
+    }
+
+    fn next_char(&mut self) -> Result<char> {
+        match decode(self.pattern, self.position) {
+            Some((c, len)) => {
+                self.position += len;
+                Ok(c)
+            }
+            None => self.error(self.position, "Unexpected end of pattern"),
+        }
+    }
+
+    /// Parses a literal character, bytes of the pattern that are not valid UTF-8 match themselves
+    fn next_literal(&mut self) -> Result<CharacterType> {
+        match decode(self.pattern, self.position) {
+            Some((char::REPLACEMENT_CHARACTER, 1)) => {
+                self.position += 1;
+                Ok(CharacterType::Byte(self.pattern[self.position - 1]))
+            }
+            _ => Ok(CharacterType::Character(self.next_char()?)),
+        }
+    }
+
+    fn parse_matching_type(&mut self) -> Result<MatchingType> {
+        if self.consume(START_ANCHOR) {
+            return Ok(MatchingType::StartAnchor);
+        }
+        if self.consume(END_ANCHOR) {
+            return Ok(MatchingType::EndAnchor);
+        }
+        if self.consume_escape(WORD_BOUNDARY) {
+            return Ok(MatchingType::WordBoundary);
+        }
+        if self.consume_escape(KEEP_OUT) {
+            return Ok(MatchingType::KeepOut);
+        }
+
+        let character = self.parse_character_type()?;
+        self.parse_quantifier(character)
+    }
+
+    fn parse_quantifier(&mut self, character: CharacterType) -> Result<MatchingType> {
+        self.skip_ignored();
+        let (min, max) = if self.consume_operator(ONE_OR_MORE) {
+            (1, None)
+        } else if self.consume_operator(ZERO_OR_ONE) {
+            (0, Some(1))
+        } else if self.consume(ZERO_OR_MORE) {
+            (0, None)
+        } else if let Some(bounds) = self.parse_bounds()? {
+            bounds
+        } else {
+            return Ok(MatchingType::Simple(character));
+        };
+
+        let possessive = !self.config.basic && self.consume(POSSESSIVE);
+        let lazy = !self.config.basic && !possessive && self.consume(LAZY);
+        Ok(MatchingType::Repeated(
+            character,
+            Repetition {
+                min,
+                max,
+                possessive,
+                lazy,
+            },
+        ))
+    }
+
+    /// Parses bounded repetitions {n}, {n,} and {n,m}, braces not forming bounds are literals
+    fn parse_bounds(&mut self) -> Result<Option<(usize, Option<usize>)>> {
+        let start = self.position;
+        if !self.consume_operator(BOUNDS_START) {
+            return Ok(None);
+        }
+
+        let min = self.parse_number();
+        let max = if self.consume(BOUNDS_SEPARATOR) {
+            self.parse_number()
+        } else {
+            min
+        };
+
+        let Some(min) = min else {
+            self.position = start;
+            return Ok(None);
+        };
+        if !self.consume_operator(BOUNDS_END) {
+            self.position = start;
+            return Ok(None);
+        }
+
+        let limit = self.config.repetition_limit;
+        if min.max(max.unwrap_or(0)) > limit {
+            let message = format!("Repetition bound exceeds the limit of {}", limit);
+            return self.error(start, &message);
+        }
+        if let Some(max) = max.filter(|max| *max < min) {
+            let message = format!("Invalid repetition bounds {{{},{}}}", min, max);
+            return self.error(start, &message);
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.position;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        let digits = core::str::from_utf8(&self.pattern[start..self.position]).ok()?;
+        // numbers too large for usize are saturated and rejected by the repetition limit
+        (!digits.is_empty()).then(|| digits.parse().unwrap_or(usize::MAX))
+    }
+
+    /// Everything up to \E (or the end of the pattern) is matched literally,
+    /// a quantifier after \E applies to the last quoted character
+    fn parse_quoted(&mut self, items: &mut Vec<MatchingType>) -> Result<()> {
+        let mut last = None;
+        while !self.is_empty() && !self.consume_escape(QUOTE_END) {
+            if let Some(c) = last.replace(self.next_char()?) {
+                items.push(MatchingType::Simple(CharacterType::Character(c)));
+            }
+        }
+
+        if let Some(c) = last {
+            items.push(self.parse_quantifier(CharacterType::Character(c))?);
+        }
+        Ok(())
+    }
+
+    fn parse_character_type(&mut self) -> Result<CharacterType> {
+        if self.consume_operator(GROUP_START) {
+            self.parse_group()
+        } else if self.consume(CHARACTER_CLASS) {
+            self.parse_escape()
+        } else if self.consume(CHARACTER_WILDCARD) {
+            Ok(CharacterType::Wildcard)
+        } else if self.consume(BRACKET_START) {
+            self.parse_bracket_group()
+        } else {
+            self.next_literal()
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<CharacterType> {
+        let start = self.position - 1;
+        if self.consume(CHARACTER_ALPHA) {
+            Ok(CharacterType::Class(CharacterClass::Alpha))
+        } else if self.consume(CHARACTER_DIGIT) {
+            Ok(CharacterType::Class(CharacterClass::Digit))
+        } else if self.consume(CHARACTER_WHITESPACE) {
+            Ok(CharacterType::Class(CharacterClass::Whitespace))
+        } else if self.consume(UNICODE_PROPERTY) {
+            Ok(CharacterType::Class(self.parse_property(start)?))
+        } else if self.consume(CODE_POINT) {
+            Ok(CharacterType::Character(self.parse_code_point(start)?))
+        } else if self.peek().is_some_and(|b| matches!(b, b'1'..=b'9')) {
+            let index = (self.pattern[self.position] - b'0') as usize;
+            self.position += 1;
+            if index > self.group_count - self.group_offset {
+                return self.error(start, &format!("Invalid backreference \\{}", index));
+            }
+            Ok(CharacterType::Backreference(self.group_offset + index))
+        } else if self.peek().is_some_and(|b| b.is_ascii_alphanumeric()) {
+            let c = self.next_char()?;
+            self.error(start, &format!("Unhandled pattern: \\{}", c))
+        } else {
+            self.next_literal()
+        }
+    }
+
+    /// Parses the name of a Unicode property like {L} after \p
+    fn parse_property(&mut self, start: usize) -> Result<CharacterClass> {
+        if !self.consume(PROPERTY_START) {
+            return self.error(start, "Missing unicode property name");
+        }
+        let name_start = self.position;
+        while self.peek().is_some_and(|b| b != PROPERTY_END) {
+            self.position += 1;
+        }
+        let name = String::from_utf8_lossy(&self.pattern[name_start..self.position]).to_string();
+        if !self.consume(PROPERTY_END) {
+            return self.error(start, "Missing end of unicode property");
+        }
+
+        match name.as_str() {
+            "L" => Ok(CharacterClass::Letter),
+            "N" => Ok(CharacterClass::Number),
+            "P" => Ok(CharacterClass::Punctuation),
+            _ => self.error(start, &format!("Unknown unicode property '{}'", name)),
+        }
+    }
+
+    /// Parses the hexadecimal code point like {1F600} after \u, surrogates are rejected
+    fn parse_code_point(&mut self, start: usize) -> Result<char> {
+        if !self.consume(CODE_POINT_START) {
+            return self.error(start, "Missing code point");
+        }
+        let digits_start = self.position;
+        while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+            self.position += 1;
+        }
+        let digits = core::str::from_utf8(&self.pattern[digits_start..self.position]).unwrap_or("");
+        if !self.consume(CODE_POINT_END) {
+            return self.error(start, "Missing end of code point");
+        }
+
+        match u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(c) => Ok(c),
+            None => self.error(start, &format!("Invalid code point '{}'", digits)),
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<CharacterType> {
+        let start = self.position - if self.config.basic { 2 } else { 1 };
+        self.group_count += 1;
+        let index = self.group_count;
+        if !self.config.basic && self.consume(GROUP_EXTENSION) {
+            self.parse_group_name(start, index)?;
+        }
+
+        let items = self.parse_alternation()?;
+        if !self.consume_operator(GROUP_END) {
+            return Err(Error::UnbalancedParen { position: start });
+        }
+
+        Ok(CharacterType::Group(Group { index, items }))
+    }
+
+    /// Parses the name of a named group (?<name>...) or (?P<name>...) after the question mark
+    fn parse_group_name(&mut self, start: usize, index: usize) -> Result<()> {
+        self.consume(GROUP_NAME_PREFIX);
+        if !self.consume(GROUP_NAME_START) {
+            return self.error(start, "Unsupported group syntax");
+        }
+
+        let name_start = self.position;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            self.position += 1;
+        }
+        let name = String::from_utf8_lossy(&self.pattern[name_start..self.position]).to_string();
+        if !self.consume(GROUP_NAME_END) {
+            return self.error(start, "Missing end of group name");
+        }
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return self.error(start, &format!("Invalid group name '{}'", name));
+        }
+        if self.names.iter().any(|(other, _)| *other == name) {
+            return self.error(start, &format!("Duplicate group name '{}'", name));
+        }
+
+        self.names.push((name, index));
+        Ok(())
+    }
+
+    fn parse_bracket_group(&mut self) -> Result<CharacterType> {
+        let start = self.position - 1;
+        let negated = self.consume(BRACKET_NEGATION);
+
+        let mut characters = Vec::new();
+        let mut ranges = Vec::new();
+        let mut classes = Vec::new();
+        while !self.consume(BRACKET_END) {
+            if self.is_empty() {
+                return Err(Error::UnbalancedBracket { position: start });
+            }
+            if self.consume_escape(CHARACTER_DIGIT) {
+                classes.push(CharacterClass::Digit);
+                continue;
+            }
+            if self.consume_escape(CHARACTER_WHITESPACE) {
+                classes.push(CharacterClass::Whitespace);
+                continue;
+            }
+            if self.consume_escape(CHARACTER_ALPHA) {
+                classes.push(CharacterClass::Alpha);
+                continue;
+            }
+            if self.consume_escape(UNICODE_PROPERTY) {
+                classes.push(self.parse_property(self.position - 2)?);
+                continue;
+            }
+
+            let range_start = self.position;
+            let c = self.parse_bracket_character()?;
+            // a - at the start or end of the group is a literal
+            let is_range = self.peek() == Some(BRACKET_RANGE)
+                && !matches!(
+                    self.pattern.get(self.position + 1),
+                    None | Some(&BRACKET_END)
+                );
+            if !is_range {
+                characters.push(c);
+                continue;
+            }
+
+            self.position += 1;
+            let end = self.parse_bracket_character()?;
+            if c > end {
+                let message = format!("Invalid bracket range {}-{}", c, end);
+                return self.error(range_start, &message);
+            }
+            ranges.push((c, end));
+        }
+
+        Ok(CharacterType::Bracket(BracketGroup {
+            negated,
+            characters,
+            ranges,
+            classes,
+        }))
+    }
+
+    /// Parses a character of a bracket group, a backslash makes the next character a literal,
+    /// backreferences like \1 are rejected as they can not match a single character
+    fn parse_bracket_character(&mut self) -> Result<char> {
+        let start = self.position;
+        if !self.consume(CHARACTER_CLASS) {
+            return self.next_char();
+        }
+
+        match self.next_char()? {
+            'u' => self.parse_code_point(start),
+            c @ '1'..='9' => {
+                let message = format!("Backreference \\{} is not allowed in bracket groups", c);
+                self.error(start, &message)
+            }
+            c if c.is_ascii_alphanumeric() => {
+                self.error(start, &format!("Unhandled pattern: \\{}", c))
+            }
+            c => Ok(c),
+        }
+    }
+}
+
+/// Surrounds the items with word boundaries so they only match whole words
+fn add_word_boundaries(items: &mut Vec<MatchingType>) {
+    items.insert(0, MatchingType::WordBoundary);
+    items.push(MatchingType::WordBoundary);
+}
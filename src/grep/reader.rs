@@ -0,0 +1,63 @@
+use std::io::BufRead;
+use std::io::Write;
+
+use super::error::Result;
+use super::Regex;
+
+impl Regex {
+    /// Returns the 1-based line number and content of the first matching line
+    pub fn first_match_line<R: BufRead>(&self, reader: R) -> Result<Option<(usize, String)>> {
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if self.try_find(&line)?.is_some() {
+                return Ok(Some((index + 1, line)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the 1-based line and character column and the text of every match of the reader
+    pub fn matches_with_position<R: BufRead>(
+        &self,
+        reader: R,
+    ) -> Result<Vec<(usize, usize, String)>> {
+        let mut matches = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut captures = self.captures_iter(&line);
+            while let Some(m) = captures.try_next()?.and_then(|captures| captures.get(0)) {
+                let column = line[..m.start()].chars().count() + 1;
+                matches.push((index + 1, column, m.as_str().to_string()));
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Writes the input with every match replaced to the writer, see `replace_all`
+    pub fn replace_all_to<W: Write>(
+        &self,
+        input: &str,
+        replacement: &str,
+        mut output: W,
+    ) -> Result<()> {
+        self.replace_with(input, replacement, usize::MAX, |part| {
+            Ok(output.write_all(part.as_bytes())?)
+        })
+    }
+
+    /// Lazily reads the lines and yields only the matching ones, reading and matching errors
+    /// are yielded in place of the line they occurred in
+    pub fn matching_lines<'r, R: BufRead + 'r>(
+        &'r self,
+        reader: R,
+    ) -> impl Iterator<Item = Result<String>> + 'r {
+        reader.lines().filter_map(move |line| {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            let found = self.try_find(&line).map(|m| m.is_some());
+            found.map(|found| found.then_some(line)).transpose()
+        })
+    }
+}
@@ -0,0 +1,770 @@
+use std::collections::HashMap;
+use std::ops::Index;
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::error::Error;
+use super::error::Result;
+use super::matcher::Matcher;
+use super::matcher::Slots;
+use super::parser::Parser;
+use super::Config;
+use super::Input;
+use super::MatchingType;
+use super::Pattern;
+
+/// A compiled pattern that can be matched against multiple inputs
+#[derive(Clone, Debug)]
+pub struct Regex {
+    pattern: Pattern,
+    /// Start anchored patterns can only match at the beginning of the input
+    anchored_start: bool,
+    /// Names of the named groups and their index, shared with all captures
+    names: Arc<[(String, usize)]>,
+    /// Literal every match starts with, used to skip start positions that can not match
+    prefix: Vec<u8>,
+    /// Bytes every match starts with if the pattern can not match the empty input, used to
+    /// skip start positions like the prefix
+    first_bytes: Option<[bool; 256]>,
+    config: Config,
+}
+
+/// Builder to compile a pattern with non default options
+///
+/// By default the input is treated as a single buffer: a wildcard (.) matches
+/// every character except a newline (or the configured line terminator), classes
+/// like \d and \w never match a newline and bracket groups only do when it is
+/// listed or the group is negated.
+/// Anchors (^ and $) only match at the beginning and end of the whole input
+/// unless `multi_line` is enabled.
+#[derive(Clone, Debug)]
+pub struct RegexBuilder {
+    /// Patterns that are compiled as alternatives of one pattern
+    patterns: Vec<Vec<u8>>,
+    config: Config,
+}
+
+/// A single match of a pattern inside an input
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Match<'a> {
+    input: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// The match of a pattern and the input captured by each of its groups
+#[derive(Clone, Debug)]
+pub struct Captures<'a> {
+    input: &'a str,
+    slots: Slots,
+    names: Arc<[(String, usize)]>,
+}
+
+/// Iterator over all non overlapping captures of a pattern in an input
+#[derive(Debug)]
+pub struct CaptureMatches<'r, 'a> {
+    regex: &'r Regex,
+    input: &'a str,
+    position: usize,
+    /// End of the previous match if it was empty, to not report it twice
+    last_empty: Option<usize>,
+}
+
+/// Part of a split input, either the text between delimiters or a matched delimiter
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SplitItem<'a> {
+    Text(&'a str),
+    Delimiter(Match<'a>),
+}
+
+/// Iterator over all non overlapping matches of a pattern in an input
+#[derive(Debug)]
+pub struct Matches<'r, 'a>(CaptureMatches<'r, 'a>);
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    /// Compiles a pattern that may contain bytes which are not valid UTF-8, such a byte
+    /// only matches the same byte in inputs passed to `is_match_bytes`
+    pub fn from_bytes(pattern: &[u8]) -> Result<Regex> {
+        RegexBuilder::from_bytes(pattern).build()
+    }
+
+    fn from_pattern(pattern: Pattern, config: Config) -> Regex {
+        let anchored_start =
+            !config.multi_line && matches!(pattern.items.first(), Some(MatchingType::StartAnchor));
+        let prefix = pattern.literal_prefix();
+        let first_bytes = pattern.first_bytes(&config);
+        Regex {
+            first_bytes,
+            names: pattern.names.clone().into(),
+            pattern,
+            anchored_start,
+            prefix,
+            config,
+        }
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.find(input).is_some()
+    }
+
+    /// Returns if the pattern matches inside of the byte range of the input, e.g. a field found
+    /// before, anchors match at the range boundaries and ranges not on character boundaries
+    /// never match
+    pub fn is_match_in(&self, input: &str, range: Range<usize>) -> bool {
+        input.get(range).is_some_and(|field| self.is_match(field))
+    }
+
+    /// Returns if the pattern matches the whole input, errors are reported as no match
+    pub fn is_full_match(&self, input: &str) -> bool {
+        self.try_full_match(input).unwrap_or(false)
+    }
+
+    /// Returns if the pattern matches the whole input or an error if matching was aborted
+    pub fn try_full_match(&self, input: &str) -> Result<bool> {
+        Matcher::new(input.as_bytes(), &self.config).full_match(&self.pattern)
+    }
+
+    /// Returns the leftmost match, errors during matching are reported as no match
+    pub fn find<'a>(&self, input: &'a str) -> Option<Match<'a>> {
+        self.try_find(input).ok().flatten()
+    }
+
+    /// Returns the leftmost match or an error if matching was aborted, e.g. by the step limit
+    pub fn try_find<'a>(&self, input: &'a str) -> Result<Option<Match<'a>>> {
+        self.try_find_at(input, 0)
+    }
+
+    /// Returns the leftmost match starting at or after the byte offset `start`
+    pub fn try_find_at<'a>(&self, input: &'a str, start: usize) -> Result<Option<Match<'a>>> {
+        if !input.is_char_boundary(start) {
+            return Err(Error::InvalidOffset(start));
+        }
+        let slots = self.search(input, start)?;
+        Ok(slots.map(|(_, slots)| self.new_captures(input, slots).whole()))
+    }
+
+    /// Returns if the pattern matches anywhere in a byte input, e.g. of a binary protocol
+    pub fn is_match_bytes(&self, input: &[u8]) -> bool {
+        self.try_is_match_bytes(input).unwrap_or(false)
+    }
+
+    /// Returns if the pattern matches the byte input or an error if matching was aborted
+    pub fn try_is_match_bytes(&self, input: &[u8]) -> Result<bool> {
+        Ok(self.search_bytes(input, 0, |_| true)?.is_some())
+    }
+
+    /// Returns if the pattern matches anywhere in an input that implements `Input`, matches
+    /// only start at its character boundaries
+    pub fn is_match_input<I: Input + ?Sized>(&self, input: &I) -> bool {
+        let slots = self.search_bytes(input, 0, |i| input.is_char_boundary(i));
+        matches!(slots, Ok(Some(_)))
+    }
+
+    /// Returns if the pattern matches the byte input at or after the offset `start`,
+    /// offsets outside of the input never match
+    pub fn is_match_bytes_at(&self, input: &[u8], start: usize) -> bool {
+        start <= input.len() && matches!(self.search_bytes(input, start, |_| true), Ok(Some(_)))
+    }
+
+    /// Returns the end of the shortest match starting at the leftmost position a match exists
+    pub fn shortest_match(&self, input: &str) -> Option<usize> {
+        // the attempt start and not the match start as \K may have moved the latter
+        let (start, _) = self.search(input, 0).ok().flatten()?;
+        self.shortest_match_at(input, start)
+    }
+
+    /// Returns the end of the shortest match starting at exactly the byte offset `start`,
+    /// e.g. for lexers picking the shortest accepting prefix of the remaining input
+    pub fn shortest_match_at(&self, input: &str, start: usize) -> Option<usize> {
+        if !input.is_char_boundary(start) {
+            return None;
+        }
+        let mut matcher = Matcher::new(input.as_bytes(), &self.config);
+        matcher
+            .shortest_match_at(&self.pattern, start)
+            .ok()
+            .flatten()
+    }
+
+    /// Matches the pattern at the start of the input and returns the remaining input after
+    /// the match, e.g. for parsers consuming tokens from left to right
+    pub fn match_prefix<'a>(&self, input: &'a str) -> Option<&'a str> {
+        let mut matcher = Matcher::new(input.as_bytes(), &self.config);
+        let slots = matcher.match_at(&self.pattern, 0).ok().flatten()?;
+        let (_, end) = slots[0]?;
+        Some(&input[end..])
+    }
+
+    pub fn find_iter<'r, 'a>(&'r self, input: &'a str) -> Matches<'r, 'a> {
+        Matches(self.captures_iter(input))
+    }
+
+    /// Returns the matches that each start exactly where the previous one ended, beginning at
+    /// the start of the input, and stops at the first position without a match like a lexer
+    pub fn tokenize<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Match<'a>> + 'a {
+        let mut matcher = Matcher::new(input.as_bytes(), &self.config);
+        let mut position = Some(0);
+        core::iter::from_fn(move || {
+            let start = position?;
+            let slots = matcher.match_at(&self.pattern, start).ok().flatten()?;
+            let whole = self.new_captures(input, slots).whole();
+            // an empty token would be matched again at the same position forever
+            position = (whole.end > start).then_some(whole.end);
+            Some(whole)
+        })
+    }
+
+    /// Returns the non overlapping matches that start at the beginning of a word, e.g. to find
+    /// identifiers starting with a prefix but not the same text in the middle of a word
+    pub fn find_word_starts<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Match<'a>> + 'a {
+        let boundaries = Matcher::new(input.as_bytes(), &self.config);
+        let mut position = 0;
+        core::iter::from_fn(move || {
+            if position > input.len() {
+                return None;
+            }
+            let is_start = |i| input.is_char_boundary(i) && boundaries.is_word_start(i);
+            let (_, slots) = self
+                .search_bytes(input.as_bytes(), position, is_start)
+                .ok()??;
+            let whole = self.new_captures(input, slots).whole();
+            // matches start at a word character so empty matches are followed by one
+            position = if whole.start == whole.end {
+                whole.end + 1
+            } else {
+                whole.end
+            };
+            Some(whole)
+        })
+    }
+
+    /// Returns owned copies of the texts of all non overlapping matches
+    pub fn matches_owned(&self, input: &str) -> Vec<String> {
+        self.find_iter(input)
+            .map(|m| m.as_str().to_string())
+            .collect()
+    }
+
+    /// Returns the rightmost of the non overlapping matches, e.g. to parse trailing fields
+    pub fn find_last<'a>(&self, input: &'a str) -> Option<Match<'a>> {
+        self.find_iter(input).last()
+    }
+
+    /// Returns the length in bytes of the leftmost match, e.g. to compare greedy and lazy patterns
+    pub fn match_len(&self, input: &str) -> Option<usize> {
+        self.find(input).map(|m| m.end() - m.start())
+    }
+
+    /// Returns the number of non overlapping matches in the input
+    pub fn count(&self, input: &str) -> usize {
+        self.find_iter(input).count()
+    }
+
+    /// Returns the maximum number of bytes a match can consume or None if it is unbounded,
+    /// e.g. because of + or *, wildcards and classes count as four bytes (one UTF-8 character)
+    pub fn max_match_len(&self) -> Option<usize> {
+        self.pattern.max_len()
+    }
+
+    /// Explains the result of matching the input for debugging patterns, failures report the
+    /// furthest position a match attempt got to and the pattern item that did not match there
+    pub fn explain_match(&self, input: &str) -> String {
+        match self.try_find(input) {
+            Ok(Some(m)) => {
+                return format!("Matched {:?} at {}..{}", m.as_str(), m.start(), m.end())
+            }
+            Ok(None) => {}
+            Err(error) => return format!("Matching was aborted: {}", error),
+        }
+
+        let mut matcher = Matcher::explaining(input.as_bytes(), &self.config);
+        let last_start = if self.anchored_start { 0 } else { input.len() };
+        for start in (0..=last_start).filter(|i| input.is_char_boundary(*i)) {
+            let _ = matcher.match_at(&self.pattern, start);
+        }
+        match matcher.furthest_item() {
+            Some((position, item)) => format!(
+                "No match, the furthest attempt failed at {} before {:?} (position {})",
+                item,
+                &input[position..],
+                position
+            ),
+            None => "No match".to_string(),
+        }
+    }
+
+    /// Returns if the pattern can match the empty input, e.g. a* or ^$, such patterns produce
+    /// empty matches that callers iterating over matches may need to handle
+    pub fn matches_empty(&self) -> bool {
+        self.pattern
+            .is_nullable(&self.pattern.items, &mut Vec::new())
+    }
+
+    /// Removes all matches directly at the start and at the end of the input, like
+    /// `str::trim_matches` but matches inside of the input are kept
+    pub fn trim_matches<'a>(&self, input: &'a str) -> &'a str {
+        let mut matcher = Matcher::new(input.as_bytes(), &self.config);
+        let mut start = 0;
+        while let Ok(Some(slots)) = matcher.match_at(&self.pattern, start) {
+            match slots[0] {
+                Some((_, end)) if end > start => start = end,
+                _ => break,
+            }
+        }
+
+        // the leftmost start of a match ending at the end removes the longest trailing match
+        let mut end = input.len();
+        while let Some(trailing) = (start..end).find(|i| {
+            input.is_char_boundary(*i)
+                && matches!(matcher.match_between(&self.pattern, *i, end), Ok(true))
+        }) {
+            end = trailing;
+        }
+        &input[start..end]
+    }
+
+    /// Splits the input at every match and returns the texts between the delimiters
+    pub fn split<'a>(&'a self, input: &'a str) -> Vec<&'a str> {
+        let mut texts = Vec::new();
+        let mut last = 0;
+        for delimiter in self.find_iter(input) {
+            texts.push(&input[last..delimiter.start]);
+            last = delimiter.end;
+        }
+        texts.push(&input[last..]);
+        texts
+    }
+
+    /// Splits the input at every match and returns the texts alternating with the delimiters
+    pub fn split_with_matches<'a>(&'a self, input: &'a str) -> Vec<SplitItem<'a>> {
+        let mut items = Vec::new();
+        let mut last = 0;
+        for delimiter in self.find_iter(input) {
+            items.push(SplitItem::Text(&input[last..delimiter.start]));
+            items.push(SplitItem::Delimiter(delimiter));
+            last = delimiter.end;
+        }
+        items.push(SplitItem::Text(&input[last..]));
+        items
+    }
+
+    /// Replaces the leftmost match with the replacement, see `replace_all`
+    pub fn replace(&self, input: &str, replacement: &str) -> Result<String> {
+        self.replace_matches(input, replacement, 1)
+    }
+
+    /// Replaces every match with the replacement, in which $n is replaced by the input
+    /// captured by group n (nothing if it did not match) and $$ by a single $,
+    /// references to groups the pattern does not have are an error unless configured otherwise
+    pub fn replace_all(&self, input: &str, replacement: &str) -> Result<String> {
+        self.replace_matches(input, replacement, usize::MAX)
+    }
+
+    fn replace_matches(&self, input: &str, replacement: &str, limit: usize) -> Result<String> {
+        let mut replaced = String::with_capacity(input.len());
+        self.replace_with(input, replacement, limit, |part| {
+            replaced.push_str(part);
+            Ok(())
+        })?;
+        Ok(replaced)
+    }
+
+    /// Passes the parts of the input with up to `limit` matches replaced to the output one by one
+    pub(super) fn replace_with<F: FnMut(&str) -> Result<()>>(
+        &self,
+        input: &str,
+        replacement: &str,
+        limit: usize,
+        mut output: F,
+    ) -> Result<()> {
+        let mut expanded = String::new();
+        let mut last = 0;
+        let mut matches = self.captures_iter(input);
+        for _ in 0..limit {
+            let Some(captures) = matches.try_next()? else {
+                break;
+            };
+            let whole = captures.whole();
+            output(&input[last..whole.start])?;
+            expanded.clear();
+            captures.expand(replacement, &self.config, &mut expanded)?;
+            output(&expanded)?;
+            last = whole.end;
+        }
+        output(&input[last..])
+    }
+
+    /// Returns the leftmost match together with the input captured by each group
+    pub fn captures<'a>(&self, input: &'a str) -> Option<Captures<'a>> {
+        let (_, slots) = self.search(input, 0).ok().flatten()?;
+        Some(self.new_captures(input, slots))
+    }
+
+    /// Returns the text captured by each named group of the leftmost match by the group name,
+    /// unnamed groups and named groups that did not take part in the match are left out
+    pub fn named_captures(&self, input: &str) -> Option<HashMap<String, String>> {
+        let captures = self.captures(input)?;
+        Some(
+            self.names
+                .iter()
+                .filter_map(|(name, index)| {
+                    let m = captures.get(*index)?;
+                    Some((name.clone(), m.as_str().to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    fn new_captures<'a>(&self, input: &'a str, slots: Slots) -> Captures<'a> {
+        Captures {
+            input,
+            slots,
+            names: self.names.clone(),
+        }
+    }
+
+    pub fn captures_iter<'r, 'a>(&'r self, input: &'a str) -> CaptureMatches<'r, 'a> {
+        CaptureMatches {
+            regex: self,
+            input,
+            position: 0,
+            last_empty: None,
+        }
+    }
+
+    /// Finds the leftmost match starting at or after `start` and returns the start of the match
+    /// attempt with the slots, which differs from the reported match start after a \K
+    fn search(&self, input: &str, start: usize) -> Result<Option<(usize, Slots)>> {
+        self.search_bytes(input.as_bytes(), start, |i| input.is_char_boundary(i))
+    }
+
+    /// Finds the leftmost match starting at or after `start` at a position accepted by `is_start`
+    fn search_bytes<I: Input + ?Sized>(
+        &self,
+        input: &I,
+        start: usize,
+        is_start: impl Fn(usize) -> bool,
+    ) -> Result<Option<(usize, Slots)>> {
+        let mut matcher = Matcher::new(input, &self.config);
+        let last_start = if self.anchored_start { 0 } else { input.len() };
+        let mut start = start;
+        while start <= last_start {
+            // jump to the next occurrence of the prefix as matches can not start anywhere else
+            if !self.prefix.is_empty() {
+                match find_bytes(input, start, &self.prefix) {
+                    Some(position) => start = position,
+                    None => break,
+                }
+            }
+            if is_start(start) && self.may_start_at(input, start) {
+                if let Some(slots) = matcher.match_at(&self.pattern, start)? {
+                    return Ok(Some((start, slots)));
+                }
+            }
+            start += 1;
+        }
+        Ok(None)
+    }
+
+    /// Returns if a match may start at the position according to its first byte
+    fn may_start_at<I: Input + ?Sized>(&self, input: &I, position: usize) -> bool {
+        match (&self.first_bytes, input.byte_at(position)) {
+            (None, _) => true,
+            (Some(bytes), Some(byte)) => bytes[byte as usize],
+            // matches that can not be empty neither start at the end of the input
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Returns the index of the top level alternative that produced the leftmost match,
+    /// patterns without alternation only consist of the alternative 0
+    pub fn matched_alternative(&self, input: &str) -> Option<usize> {
+        let (start, slots) = self.search(input, 0).ok().flatten()?;
+        let [MatchingType::Alternation(alternatives)] = self.pattern.items.as_slice() else {
+            return Some(0);
+        };
+
+        // the first alternative producing the found match, later ones may be longer with
+        // leftmost longest matching
+        let end = slots[0].map(|(_, end)| end);
+        let mut matcher = Matcher::new(input.as_bytes(), &self.config);
+        alternatives.iter().position(|alternative| {
+            let slots = matcher.match_items_at(alternative, self.pattern.group_count, start);
+            matches!(slots, Ok(Some(slots)) if slots[0].map(|(_, end)| end) == end)
+        })
+    }
+
+    /// Returns all items the pattern matches
+    pub fn matching_items<'a, I: IntoIterator<Item = &'a str>>(&self, items: I) -> Vec<&'a str> {
+        items
+            .into_iter()
+            .filter(|item| self.is_match(item))
+            .collect()
+    }
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> RegexBuilder {
+        RegexBuilder::from_bytes(pattern.as_bytes())
+    }
+
+    /// Starts building a pattern that may contain bytes which are not valid UTF-8
+    pub fn from_bytes(pattern: &[u8]) -> RegexBuilder {
+        RegexBuilder {
+            patterns: vec![pattern.to_vec()],
+            config: Config::default(),
+        }
+    }
+
+    /// Starts building a pattern that matches if any of the patterns matches, e.g. for grep -e
+    pub fn any<S: AsRef<str>>(patterns: &[S]) -> RegexBuilder {
+        RegexBuilder {
+            patterns: patterns
+                .iter()
+                .map(|pattern| pattern.as_ref().as_bytes().to_vec())
+                .collect(),
+            config: Config::default(),
+        }
+    }
+
+    /// Lets wildcards (.) also match a newline character (or the configured line terminator)
+    pub fn dot_matches_newline(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.dot_matches_newline = yes;
+        self
+    }
+
+    /// Sets the record separator wildcards do not match, e.g. a NUL byte for null data
+    pub fn line_terminator(&mut self, terminator: u8) -> &mut RegexBuilder {
+        self.config.line_terminator = terminator;
+        self
+    }
+
+    /// Adds characters that \w and \b consider part of a word besides ascii alpha numerics
+    pub fn word_characters(&mut self, characters: &str) -> &mut RegexBuilder {
+        self.config.word_characters.extend(characters.chars());
+        self
+    }
+
+    /// Sets the largest bound bounded repetitions like a{1,1000} may use
+    pub fn repetition_limit(&mut self, limit: usize) -> &mut RegexBuilder {
+        self.config.repetition_limit = limit;
+        self
+    }
+
+    /// Parses the pattern as basic instead of extended regular expression, e.g. a\+ instead of a+
+    pub fn basic(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.basic = yes;
+        self
+    }
+
+    /// Ignores unescaped whitespace and comments from # to the end of the line in the pattern,
+    /// like starting the pattern with (?x)
+    pub fn ignore_whitespace(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.ignore_whitespace = yes;
+        self
+    }
+
+    /// Lets \d match all Unicode decimal digits, e.g. Arabic-Indic digits, instead of 0-9,
+    /// and \s and properties like \p{L} all Unicode characters of their class
+    pub fn unicode(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.unicode = yes;
+        self
+    }
+
+    /// Lets ^ and $ match at the start and end of every line of the input instead of only at
+    /// the start and end of the whole input
+    pub fn multi_line(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.multi_line = yes;
+        self
+    }
+
+    /// Reports the longest of the matches starting at the leftmost position like POSIX
+    /// instead of the first one, e.g. ab for a|ab in abc
+    pub fn leftmost_longest(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.leftmost_longest = yes;
+        self
+    }
+
+    /// Matches the patterns as literal strings, e.g. a.b only matches a dot between a and b
+    /// and ^ and $ are no anchors
+    pub fn fixed_strings(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.fixed_strings = yes;
+        self
+    }
+
+    /// Only matches whole words like surrounding every pattern with word boundaries (\b),
+    /// alternatives of a pattern are all surrounded, e.g. cat|dog matches neither in category
+    pub fn whole_words(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.whole_words = yes;
+        self
+    }
+
+    /// Replaces references to groups the pattern does not have with nothing instead of failing
+    pub fn lenient_group_references(&mut self, yes: bool) -> &mut RegexBuilder {
+        self.config.lenient_group_references = yes;
+        self
+    }
+
+    pub fn build(&self) -> Result<Regex> {
+        let patterns: Vec<_> = self.patterns.iter().map(Vec::as_slice).collect();
+        let pattern = Parser::parse_any(&patterns, &self.config)?;
+        Ok(Regex::from_pattern(pattern, self.config.clone()))
+    }
+}
+
+impl<'a> Match<'a> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        &self.input[self.start..self.end]
+    }
+}
+
+impl<'a> Captures<'a> {
+    /// Returns the match of the group with the given index, the whole match has index 0
+    pub fn get(&self, index: usize) -> Option<Match<'a>> {
+        let (start, end) = (*self.slots.get(index)?)?;
+        Some(Match {
+            input: self.input,
+            start,
+            end,
+        })
+    }
+
+    /// Returns the match of the named group, e.g. "year" for (?<year>\d+)
+    pub fn name(&self, name: &str) -> Option<Match<'a>> {
+        let (_, index) = self.names.iter().find(|(other, _)| other == name)?;
+        self.get(*index)
+    }
+
+    /// Returns the number of groups including the whole match
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Appends the replacement with all group references like $1 replaced by the captured input
+    fn expand(&self, replacement: &str, config: &Config, output: &mut String) -> Result<()> {
+        let mut rest = replacement;
+        while let Some(index) = rest.find('$') {
+            output.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits > 0 {
+                let group = rest[..digits].parse().unwrap_or(usize::MAX);
+                if group >= self.len() && !config.lenient_group_references {
+                    return Err(Error::InvalidGroupReference(group));
+                }
+                if let Some(m) = self.get(group) {
+                    output.push_str(m.as_str());
+                }
+                rest = &rest[digits..];
+            } else {
+                if let Some(escaped) = rest.strip_prefix('$') {
+                    rest = escaped;
+                }
+                output.push('$');
+            }
+        }
+        output.push_str(rest);
+        Ok(())
+    }
+
+    fn whole(&self) -> Match<'a> {
+        self.get(0)
+            .expect("captures always contain the whole match")
+    }
+}
+
+/// Returns the text captured by the group with the index, panics if the group did not match
+impl<'a> Index<usize> for Captures<'a> {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.get(index)
+            .unwrap_or_else(|| panic!("no group at index {}", index))
+            .as_str()
+    }
+}
+
+/// Returns the text captured by the named group, panics if the group did not match
+impl<'a, 'n> Index<&'n str> for Captures<'a> {
+    type Output = str;
+
+    fn index(&self, name: &'n str) -> &str {
+        self.name(name)
+            .unwrap_or_else(|| panic!("no group named '{}'", name))
+            .as_str()
+    }
+}
+
+impl<'r, 'a> Iterator for CaptureMatches<'r, 'a> {
+    type Item = Captures<'a>;
+
+    fn next(&mut self) -> Option<Captures<'a>> {
+        self.try_next().ok().flatten()
+    }
+}
+
+impl<'r, 'a> CaptureMatches<'r, 'a> {
+    /// Returns the next captures or an error if matching was aborted, which ends the iteration
+    pub(super) fn try_next(&mut self) -> Result<Option<Captures<'a>>> {
+        if self.position > self.input.len() {
+            return Ok(None);
+        }
+
+        loop {
+            let slots = self.regex.search(self.input, self.position);
+            let Ok(Some((start, slots))) = slots else {
+                self.position = self.input.len() + 1;
+                return slots.map(|_| None);
+            };
+
+            let captures = self.regex.new_captures(self.input, slots);
+            let whole = captures.whole();
+            // continue after empty matches with the character after the attempt start, a \K may
+            // have moved the match behind it and a later attempt can report the same match again
+            self.position = if whole.start == whole.end {
+                let next = self.input[start..].chars().next();
+                start + next.map_or(1, char::len_utf8)
+            } else {
+                whole.end
+            };
+            if whole.start == whole.end && self.last_empty == Some(whole.end) {
+                continue;
+            }
+            self.last_empty = (whole.start == whole.end).then_some(whole.end);
+            return Ok(Some(captures));
+        }
+    }
+}
+
+impl<'r, 'a> Iterator for Matches<'r, 'a> {
+    type Item = Match<'a>;
+
+    fn next(&mut self) -> Option<Match<'a>> {
+        self.0.next().map(|captures| captures.whole())
+    }
+}
+
+/// Returns the position of the first occurrence of the needle at or after `start`
+fn find_bytes<I: Input + ?Sized>(haystack: &I, start: usize, needle: &[u8]) -> Option<usize> {
+    let last = haystack.len().checked_sub(needle.len())?;
+    (start..=last).find(|position| {
+        (0..needle.len()).all(|i| haystack.byte_at(position + i) == Some(needle[i]))
+    })
+}
@@ -2,11 +2,11 @@ use super::*;
 
 #[cfg(test)]
 mod tests {
-    use anyhow::Error;
+    use std::io::Cursor;
 
     use super::*;
 
-    fn match_result(result: Result<bool, Error>, expected: bool) {
+    fn match_result(result: Result<bool>, expected: bool) {
         match result {
             Ok(r) => assert_eq!(r, expected),
             Err(_) => panic!(""),
@@ -115,9 +115,1435 @@ mod tests {
         match_result(result, true);
     }
 
+    #[test]
+    fn match_zero_or_more_times() {
+        match_result(match_pattern("dg", "^do*g$"), true);
+        match_result(match_pattern("dooog", "^do*g$"), true);
+    }
+
+    #[test]
+    fn match_greedy_gives_back() {
+        let result = match_pattern("aaa", "a+a");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn match_possessive_one_or_more_times() {
+        let result = match_pattern("aaa", "a++a");
+        match_result(result, false);
+    }
+
+    #[test]
+    fn match_possessive_zero_or_more_times() {
+        match_result(match_pattern("aaa", "a*a"), true);
+        match_result(match_pattern("aaa", "a*+a"), false);
+        match_result(match_pattern("aaab", "a*+b"), true);
+    }
+
+    #[test]
+    fn match_possessive_zero_or_one_time() {
+        match_result(match_pattern("a", "^a?a$"), true);
+        match_result(match_pattern("a", "^a?+a$"), false);
+        match_result(match_pattern("aa", "^a?+a$"), true);
+    }
+
     #[test]
     fn match_wildcard() {
         let result = match_pattern("dog", "d.g");
         match_result(result, true);
     }
+
+    #[test]
+    fn try_find_match() {
+        let regex = Regex::new("\\d+").unwrap();
+        let result = regex.try_find("apple 123").unwrap().unwrap();
+        assert_eq!(result.start(), 6);
+        assert_eq!(result.end(), 9);
+        assert_eq!(result.as_str(), "123");
+    }
+
+    #[test]
+    fn try_find_no_match() {
+        let regex = Regex::new("\\d").unwrap();
+        assert!(matches!(regex.try_find("apple"), Ok(None)));
+    }
+
+    #[test]
+    fn try_find_step_limit() {
+        let pattern = format!("{}{}", "a?".repeat(30), "a".repeat(30));
+        let regex = Regex::new(&pattern).unwrap();
+        assert!(matches!(
+            regex.try_find(&"a".repeat(30)),
+            Err(Error::StepLimitExceeded)
+        ));
+        assert!(regex.find(&"a".repeat(30)).is_none());
+    }
+
+    #[test]
+    fn try_find_repeated_groups_without_stack_overflow() {
+        let input = "ab".repeat(50_000);
+        for pattern in ["(ab)+", "(a|b)*c", "(a|b)+$"] {
+            let regex = Regex::new(pattern).unwrap();
+            assert!(matches!(
+                regex.try_find(&input),
+                Ok(_) | Err(Error::DepthLimitExceeded) | Err(Error::StepLimitExceeded)
+            ));
+        }
+        assert!(matches!(
+            Regex::new("(ab)+").unwrap().try_find(&input),
+            Err(Error::DepthLimitExceeded)
+        ));
+        // shorter inputs still match, unoptimized builds nest about 200 repetitions of (ab) in
+        // the stack limit and optimized builds about 1000
+        let input = "ab".repeat(50);
+        let m = Regex::new("(ab)+")
+            .unwrap()
+            .try_find(&input)
+            .unwrap()
+            .unwrap();
+        assert_eq!(m.end(), input.len());
+        match_result(match_pattern(&format!("{}c", input), "(a|b)*c"), true);
+    }
+
+    #[test]
+    fn match_escaped_meta_characters() {
+        let result = match_pattern("1+1=2", "1\\+1");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn escape_meta_characters() {
+        let escaped = escape("a.b*c");
+        assert_eq!(escaped, "a\\.b\\*c");
+
+        let pattern = format!("^{}$", escaped);
+        match_result(match_pattern("a.b*c", &pattern), true);
+        match_result(match_pattern("axb*c", &pattern), false);
+
+        let literal = "a b#c\td\ne";
+        let escaped = escape(literal);
+        assert_eq!(escaped, "a\\ b\\#c\\\td\\\ne");
+        for extended in [false, true] {
+            let regex = RegexBuilder::new(&format!("^{}$", escaped))
+                .ignore_whitespace(extended)
+                .build()
+                .unwrap();
+            assert!(regex.is_match(literal));
+            assert!(!regex.is_match("abc"));
+        }
+    }
+
+    #[test]
+    fn match_quoted_literal() {
+        let result = match_pattern("a.b", "\\Qa.b\\E");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn match_no_quoted_literal() {
+        let result = match_pattern("axb", "\\Qa.b\\E");
+        match_result(result, false);
+    }
+
+    #[test]
+    fn match_quoted_literal_with_quantifier() {
+        let result = match_pattern("a+bb", "^\\Qa+b\\E+$");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn match_start_anchor_only_at_start() {
+        let anchored = Regex::new("^\\d+").unwrap();
+        let unanchored = Regex::new("\\d+").unwrap();
+
+        for input in ["a1", "apple 123", "  42"] {
+            assert!(!anchored.is_match(input));
+            assert!(unanchored.is_match(input));
+        }
+        assert_eq!(anchored.find("42 apples").unwrap().as_str(), "42");
+    }
+
+    #[test]
+    fn match_no_wildcard_across_newline() {
+        let result = match_pattern("foo\nbar", "foo.bar");
+        match_result(result, false);
+    }
+
+    #[test]
+    fn match_wildcard_across_newline() {
+        let regex = RegexBuilder::new("foo.bar")
+            .dot_matches_newline(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("foo\nbar"));
+    }
+
+    #[test]
+    fn match_negative_group_across_newline() {
+        let result = match_pattern("foo\nbar", "foo[^x]bar");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn match_anchors_on_whole_buffer() {
+        match_result(match_pattern("foo\nbar", "^bar"), false);
+        match_result(match_pattern("foo\nbar", "foo$"), false);
+    }
+
+    #[test]
+    fn first_match_line() {
+        let regex = Regex::new("\\d+").unwrap();
+        let reader = Cursor::new("apple\nbanana\n3 dogs\n4 cats\n");
+        let result = regex.first_match_line(reader).unwrap();
+        assert_eq!(result, Some((3, "3 dogs".to_string())));
+    }
+
+    #[test]
+    fn first_match_line_no_match() {
+        let regex = Regex::new("\\d+").unwrap();
+        let reader = Cursor::new("apple\nbanana\n");
+        assert_eq!(regex.first_match_line(reader).unwrap(), None);
+    }
+
+    #[test]
+    fn match_group() {
+        match_result(match_pattern("abab", "^(ab)+$"), true);
+        match_result(match_pattern("aba", "^(ab)+$"), false);
+    }
+
+    #[test]
+    fn match_group_backtracking() {
+        let result = match_pattern("abcabc!", "(\\w+)+!");
+        match_result(result, true);
+    }
+
+    #[test]
+    fn captures_groups() {
+        let regex = Regex::new("(\\w+)@(\\w+)?").unwrap();
+        let captures = regex.captures("mail: joe@example").unwrap();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures.get(0).unwrap().as_str(), "joe@example");
+        assert_eq!(captures.get(1).unwrap().as_str(), "joe");
+        assert_eq!(captures.get(2).unwrap().as_str(), "example");
+
+        let captures = regex.captures("joe@").unwrap();
+        assert!(captures.get(2).is_none());
+    }
+
+    #[test]
+    fn captures_iter() {
+        let regex = Regex::new("(\\d)").unwrap();
+        let digits: Vec<_> = regex
+            .captures_iter("a1b2c3")
+            .map(|c| c.get(1).unwrap().as_str())
+            .collect();
+        assert_eq!(digits, ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn capture_table_records() {
+        let table = capture_table("alice,30;bob,25;eve,41;", "(\\w+),(\\d+);").unwrap();
+        let expected: Vec<Vec<Option<String>>> = [["alice", "30"], ["bob", "25"], ["eve", "41"]]
+            .iter()
+            .map(|row| row.iter().map(|value| Some(value.to_string())).collect())
+            .collect();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn match_word_boundary() {
+        match_result(match_pattern("a cat", "\\bcat\\b"), true);
+        match_result(match_pattern("category", "\\bcat\\b"), false);
+    }
+
+    #[test]
+    fn match_custom_word_characters() {
+        let default = Regex::new("^\\w+$").unwrap();
+        assert!(!default.is_match("a-b"));
+
+        let custom = RegexBuilder::new("^\\w+$")
+            .word_characters("-")
+            .build()
+            .unwrap();
+        assert!(custom.is_match("a-b"));
+    }
+
+    #[test]
+    fn match_custom_word_boundary() {
+        let default = Regex::new("\\ba\\b").unwrap();
+        assert!(default.is_match("a-b"));
+
+        let custom = RegexBuilder::new("\\ba\\b")
+            .word_characters("-")
+            .build()
+            .unwrap();
+        assert!(!custom.is_match("a-b"));
+    }
+
+    #[test]
+    fn find_iter_matches() {
+        let regex = Regex::new("\\d+").unwrap();
+        let matches: Vec<_> = regex.find_iter("1 22 333").map(|m| m.as_str()).collect();
+        assert_eq!(matches, ["1", "22", "333"]);
+    }
+
+    #[test]
+    fn count_matches() {
+        let regex = Regex::new("\\d+").unwrap();
+        assert_eq!(regex.count("1 22 333"), 3);
+        assert_eq!(regex.count("no digits"), 0);
+    }
+
+    #[test]
+    fn match_alternation() {
+        match_result(match_pattern("a dog", "cat|dog"), true);
+        match_result(match_pattern("a bird", "cat|dog"), false);
+        match_result(match_pattern("dogs", "^(cat|dog)s$"), true);
+        match_result(match_pattern("cows", "^(cat|dog)s$"), false);
+    }
+
+    #[test]
+    fn matched_alternative() {
+        let regex = Regex::new("cat|dog|bird").unwrap();
+        assert_eq!(regex.matched_alternative("cat"), Some(0));
+        assert_eq!(regex.matched_alternative("dog"), Some(1));
+        assert_eq!(regex.matched_alternative("a bird"), Some(2));
+        assert_eq!(regex.matched_alternative("cow"), None);
+    }
+
+    #[test]
+    fn matched_alternative_without_alternation() {
+        let regex = Regex::new("dog").unwrap();
+        assert_eq!(regex.matched_alternative("dog"), Some(0));
+        assert_eq!(regex.matched_alternative("cat"), None);
+    }
+
+    #[test]
+    fn match_bounded_repetition() {
+        match_result(match_pattern("aaa", "a{1,5}"), true);
+        match_result(match_pattern("bbb", "a{1,5}"), false);
+        match_result(match_pattern("aa", "^a{2}$"), true);
+        match_result(match_pattern("aaa", "^a{2}$"), false);
+        match_result(match_pattern("aaaa", "^a{2,}$"), true);
+        match_result(match_pattern("a", "^a{2,}$"), false);
+    }
+
+    #[test]
+    fn match_literal_braces() {
+        match_result(match_pattern("a{b}", "a{b}"), true);
+        match_result(match_pattern("a{", "a{"), true);
+    }
+
+    #[test]
+    fn reject_invalid_repetition_bounds() {
+        assert!(Regex::new("a{3,1}").is_err());
+    }
+
+    #[test]
+    fn reject_repetition_above_limit() {
+        assert!(Regex::new("a{1,100000000}").is_err());
+
+        let regex = RegexBuilder::new("^a{1,100000000}$")
+            .repetition_limit(100_000_000)
+            .build()
+            .unwrap();
+        assert!(regex.is_match(&"a".repeat(1000)));
+    }
+
+    #[test]
+    fn split_with_matches() {
+        let regex = Regex::new("[,;]").unwrap();
+        let items: Vec<_> = regex
+            .split_with_matches("a,b;c")
+            .into_iter()
+            .map(|item| match item {
+                SplitItem::Text(text) => format!("text {}", text),
+                SplitItem::Delimiter(m) => format!("delimiter {} at {}", m.as_str(), m.start()),
+            })
+            .collect();
+        assert_eq!(
+            items,
+            [
+                "text a",
+                "delimiter , at 1",
+                "text b",
+                "delimiter ; at 3",
+                "text c"
+            ]
+        );
+    }
+
+    #[test]
+    fn match_empty_pattern() {
+        match_result(match_pattern("apple", ""), true);
+        match_result(match_pattern("", ""), true);
+    }
+
+    #[test]
+    fn match_single_character_patterns() {
+        match_result(match_pattern("apple", "^"), true);
+        match_result(match_pattern("apple", "$"), true);
+        match_result(match_pattern("", "^"), true);
+        match_result(match_pattern("", "$"), true);
+        match_result(match_pattern("apple", "."), true);
+        match_result(match_pattern("", "."), false);
+        match_result(match_pattern("apple", "x"), false);
+    }
+
+    #[test]
+    fn error_parse() {
+        let result = Regex::new("a[bc");
+        assert!(matches!(
+            result,
+            Err(Error::UnbalancedBracket { position: 1 })
+        ));
+
+        let result = Regex::new("ab\\x");
+        assert!(matches!(result, Err(Error::Parse { position: 2, .. })));
+    }
+
+    #[test]
+    fn error_invalid_offset() {
+        let regex = Regex::new("b").unwrap();
+        assert!(matches!(
+            regex.try_find_at("abc", 10),
+            Err(Error::InvalidOffset(10))
+        ));
+        assert!(matches!(
+            regex.try_find_at("éb", 1),
+            Err(Error::InvalidOffset(1))
+        ));
+        assert_eq!(regex.try_find_at("abcb", 2).unwrap().unwrap().start(), 3);
+    }
+
+    #[test]
+    fn match_no_wildcard_across_line_terminator() {
+        let regex = RegexBuilder::new("a.b")
+            .line_terminator(b'\0')
+            .build()
+            .unwrap();
+        assert!(!regex.is_match("a\0b"));
+        assert!(regex.is_match("a\nb"));
+    }
+
+    #[test]
+    fn matching_items() {
+        let regex = Regex::new("^c\\w+t$").unwrap();
+        let words = vec!["cat", "dog", "carrot", "cab", "coat"];
+        assert_eq!(regex.matching_items(words), ["cat", "carrot", "coat"]);
+    }
+
+    #[test]
+    fn match_literal_pipe_in_bracket_group() {
+        match_result(match_pattern("|", "^[a|b]$"), true);
+        match_result(match_pattern("a", "^[a|b]$"), true);
+        match_result(match_pattern("b", "^[a|b]$"), true);
+        match_result(match_pattern("c", "^[a|b]$"), false);
+    }
+
+    #[test]
+    fn match_alternation_only_outside_bracket_group() {
+        match_result(match_pattern("|", "^(a|b)$"), false);
+        match_result(match_pattern("b", "^(a|b)$"), true);
+    }
+
+    #[test]
+    fn match_bytes_with_high_byte() {
+        match_result(match_bytes(b"\x01a\xffb\x02", b"a\xffb"), true);
+        match_result(match_bytes(b"\x01a\xfeb\x02", b"a\xffb"), false);
+        match_result(match_bytes(b"a\xffb", b"^a.b$"), true);
+    }
+
+    #[test]
+    fn match_byte_literal_not_inside_character() {
+        // 0xc3 is the first byte of the valid character é
+        let regex = Regex::from_bytes(b"\xc3").unwrap();
+        assert!(regex.is_match_bytes(b"\xc3"));
+        assert!(!regex.is_match_bytes("é".as_bytes()));
+        assert!(!regex.is_match("é"));
+    }
+
+    #[test]
+    fn match_backreference() {
+        match_result(match_pattern("abab", "^(ab)\\1$"), true);
+        match_result(match_pattern("abba", "^(ab)\\1$"), false);
+        match_result(match_pattern("cat and cat", "(\\w+) and \\1"), true);
+        match_result(match_pattern("cat and dog", "(\\w+) and \\1"), false);
+    }
+
+    #[test]
+    fn match_repeated_backreference() {
+        match_result(match_pattern("aaa", "(\\w)\\1+"), true);
+        match_result(match_pattern("abc", "(\\w)\\1+"), false);
+        let regex = Regex::new("(\\w)\\1+").unwrap();
+        assert_eq!(regex.find("xbbbby").unwrap().as_str(), "bbbb");
+    }
+
+    #[test]
+    fn error_backreference_to_missing_group() {
+        assert!(matches!(
+            match_pattern("aa", "(a)\\2"),
+            Err(Error::Parse { position: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn match_basic_pattern_operators() {
+        let basic = |pattern: &str| RegexBuilder::new(pattern).basic(true).build().unwrap();
+        assert!(basic("^a+$").is_match("a+"));
+        assert!(!basic("^a+$").is_match("aaa"));
+        assert!(basic("^a\\+$").is_match("aaa"));
+        assert!(basic("^(ab)?$").is_match("(ab)?"));
+        assert!(basic("^\\(ab\\)\\?$").is_match(""));
+        assert!(basic("^a{2}$").is_match("a{2}"));
+        assert!(basic("^a\\{2\\}$").is_match("aa"));
+        assert!(basic("^cat\\|dog$").is_match("dog"));
+        assert!(basic("^\\(a\\)\\1*$").is_match("aaa"));
+    }
+
+    #[test]
+    fn match_extended_pattern_operators() {
+        assert!(Regex::new("^a+$").unwrap().is_match("aaa"));
+        assert!(!Regex::new("^a+$").unwrap().is_match("a+"));
+        assert!(Regex::new("^a\\+$").unwrap().is_match("a+"));
+    }
+
+    #[test]
+    fn shortest_match() {
+        let regex = Regex::new("b\\w+").unwrap();
+        assert_eq!(regex.shortest_match("aabccc"), Some(4));
+        assert_eq!(regex.find("aabccc").unwrap().end(), 6);
+        assert_eq!(regex.shortest_match("aaa"), None);
+    }
+
+    #[test]
+    fn shortest_match_at_tokenizes_input() {
+        let regex = Regex::new("abc|ab|c|\\d+| ").unwrap();
+        let input = "abc 12ab";
+        let mut tokens = Vec::new();
+        let mut start = 0;
+        while let Some(end) = regex.shortest_match_at(input, start) {
+            tokens.push(&input[start..end]);
+            start = end;
+        }
+        assert_eq!(start, input.len());
+        assert_eq!(tokens, ["ab", "c", " ", "1", "2", "ab"]);
+    }
+
+    #[test]
+    fn find_iter_whole_words_between_boundaries() {
+        let regex = Regex::new("\\b\\w+\\b").unwrap();
+        let words: Vec<_> = regex
+            .find_iter("the quick, brown fox")
+            .map(|m| m.as_str())
+            .collect();
+        assert_eq!(words, ["the", "quick", "brown", "fox"]);
+    }
+
+    #[test]
+    fn replace_all_with_empty_replacement() {
+        let regex = Regex::new("\\d").unwrap();
+        assert_eq!(regex.replace_all("a1b2", "").unwrap(), "ab");
+        assert_eq!(regex.replace_all("12ab34", "").unwrap(), "ab");
+        assert_eq!(regex.replace_all("1234", "").unwrap(), "");
+        assert_eq!(regex.replace_all("abc", "").unwrap(), "abc");
+    }
+
+    #[test]
+    fn replace_all_with_group_references() {
+        let regex = Regex::new("(\\d)(x)?").unwrap();
+        assert_eq!(regex.replace_all("a1b2x", "<$1$2>").unwrap(), "a<1>b<2x>");
+        assert_eq!(regex.replace_all("a1", "$$1 $").unwrap(), "a$1 $");
+    }
+
+    #[test]
+    fn compile_any_matches_every_pattern() {
+        let regex = compile_any(&["^cat$", "\\d+", "x|y(z)"]).unwrap();
+        assert!(regex.is_match("cat"));
+        assert!(regex.is_match("a 42"));
+        assert!(regex.is_match("yz"));
+        assert!(regex.is_match("x"));
+        // the anchors of the first pattern do not apply to the others
+        assert!(!regex.is_match("cats"));
+        assert!(!regex.is_match("dog"));
+        assert_eq!(regex.matched_alternative("a 42"), Some(1));
+    }
+
+    #[test]
+    fn compile_any_keeps_backreferences_per_pattern() {
+        let regex = compile_any(&["(a)b", "(\\d)\\1"]).unwrap();
+        assert!(regex.is_match("33"));
+        assert!(!regex.is_match("34"));
+        assert_eq!(regex.captures("33").unwrap().get(2).unwrap().as_str(), "3");
+        assert!(compile_any(&["(a)", "\\2"]).is_err());
+        assert!(!compile_any(&[]).unwrap().is_match("a"));
+    }
+
+    #[test]
+    fn find_last_match() {
+        let regex = Regex::new("\\d+").unwrap();
+        let last = regex.find_last("1 22 333").unwrap();
+        assert_eq!((last.start(), last.end()), (5, 8));
+        assert_eq!(last.as_str(), "333");
+        assert!(regex.find_last("none").is_none());
+    }
+
+    #[test]
+    fn match_captures_and_bytes() {
+        let regex = Regex::new("(\\d+)-(\\w+)").unwrap();
+        let captures = regex.captures("id 42-abc").unwrap();
+        assert_eq!(captures.get(1).unwrap().as_str(), "42");
+        assert_eq!(captures.get(2).unwrap().as_str(), "abc");
+        match_result(match_bytes(b"\x00\x01\x02", b"\x01"), true);
+    }
+
+    #[test]
+    fn full_match_bounded_group() {
+        match_result(full_match("abab", "(ab){2}"), true);
+        match_result(full_match("ababab", "(ab){2}"), false);
+        match_result(full_match("ab", "(ab){2}"), false);
+        match_result(match_pattern("ababab", "(ab){2}"), true);
+    }
+
+    #[test]
+    fn full_match_backtracks_to_the_end() {
+        // the leftmost first match is only "a", but the second alternative matches everything
+        match_result(full_match("ab", "a|ab"), true);
+        let regex = Regex::new("(ab){1,2}c?").unwrap();
+        assert!(regex.is_full_match("ababc"));
+        assert!(!regex.is_full_match("xabab"));
+    }
+
+    #[test]
+    fn match_bracket_ranges() {
+        match_result(match_pattern("x", "^[a-z]$"), true);
+        match_result(match_pattern("X", "^[a-z]$"), false);
+        match_result(match_pattern("7", "^[a-c0-9]$"), true);
+        match_result(match_pattern("-", "^[a-]$"), true);
+        match_result(match_pattern("-", "^[-a]$"), true);
+        match_result(match_pattern("b", "^[^a-c]$"), false);
+    }
+
+    #[test]
+    fn error_reversed_bracket_range() {
+        assert!(Regex::new("[a-z]").is_ok());
+        assert!(matches!(
+            Regex::new("x[z-a]"),
+            Err(Error::Parse { position: 2, .. })
+        ));
+        let error = Regex::new("[z-a]").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid bracket range z-a at position 1");
+    }
+
+    #[test]
+    fn error_backreference_in_bracket_group() {
+        let error = Regex::new("(1)[\\1]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Backreference \\1 is not allowed in bracket groups at position 4"
+        );
+    }
+
+    #[test]
+    fn match_escaped_characters_in_bracket_group() {
+        match_result(match_pattern("]", "^[\\]]$"), true);
+        match_result(match_pattern("\\", "^[\\\\]$"), true);
+        match_result(match_pattern("1", "^[\\\\]$"), false);
+    }
+
+    #[test]
+    fn match_len_greedy_and_lazy() {
+        let input = "<a><b>";
+        assert_eq!(Regex::new("<.+>").unwrap().match_len(input), Some(6));
+        assert_eq!(Regex::new("<.+?>").unwrap().match_len(input), Some(3));
+        assert_eq!(Regex::new("<.++>").unwrap().match_len(input), None);
+        assert_eq!(Regex::new("a.*?").unwrap().match_len("abc"), Some(1));
+        assert_eq!(Regex::new("ab??").unwrap().match_len("abc"), Some(1));
+        assert_eq!(Regex::new("a{2,}?").unwrap().match_len("aaaa"), Some(2));
+    }
+
+    #[test]
+    fn match_lazy_group_repetition() {
+        let regex = Regex::new("(ab)+?(ab)*").unwrap();
+        let captures = regex.captures("ababab").unwrap();
+        assert_eq!(captures.get(0).unwrap().as_str(), "ababab");
+        assert_eq!(captures.get(1).unwrap().start(), 0);
+        assert_eq!(captures.get(2).unwrap().start(), 4);
+        assert_eq!(Regex::new("(ab)*?c").unwrap().match_len("ababc"), Some(5));
+    }
+
+    #[test]
+    fn match_anchored_repeated_alternation() {
+        match_result(match_pattern("abcdab", "^(ab|cd)+$"), true);
+        match_result(match_pattern("abx", "^(ab|cd)+$"), false);
+        match_result(match_pattern("", "^(ab|cd)+$"), false);
+        match_result(match_pattern("", "^(ab|cd)*$"), true);
+    }
+
+    #[test]
+    fn is_match_bytes_at_offsets() {
+        let regex = Regex::from_bytes(b"\xff\\d").unwrap();
+        let buffer = b"\xff1\x00\x00\xff2";
+        assert!(regex.is_match_bytes_at(buffer, 0));
+        assert!(regex.is_match_bytes_at(buffer, 1));
+        assert!(regex.is_match_bytes_at(buffer, 4));
+        assert!(!regex.is_match_bytes_at(buffer, 5));
+        assert!(!regex.is_match_bytes_at(buffer, 6));
+        assert!(!regex.is_match_bytes_at(buffer, 100));
+    }
+
+    #[test]
+    fn match_escaped_hyphen_in_bracket_group() {
+        match_result(match_pattern("-", "[a\\-z]"), true);
+        match_result(match_pattern("m", "[a-z]"), true);
+        match_result(match_pattern("m", "[a\\-z]"), false);
+        match_result(match_pattern("z", "^[a\\-z]$"), true);
+    }
+
+    #[test]
+    fn replace_leftmost_match() {
+        let regex = Regex::new("(\\d)(\\d)").unwrap();
+        assert_eq!(regex.replace("a12b34", "$2$1").unwrap(), "a21b34");
+        assert_eq!(regex.replace("none", "$2$1").unwrap(), "none");
+    }
+
+    #[test]
+    fn error_replace_invalid_group_reference() {
+        let regex = Regex::new("(\\d)(\\d)").unwrap();
+        assert!(matches!(
+            regex.replace("a12", "$5"),
+            Err(Error::InvalidGroupReference(5))
+        ));
+        assert!(matches!(
+            regex.replace_all("a12", "$3"),
+            Err(Error::InvalidGroupReference(3))
+        ));
+        // a reference to a group that did not take part in the match is valid
+        let optional = Regex::new("a(x)?").unwrap();
+        assert_eq!(optional.replace("ab", "[$1]").unwrap(), "[]b");
+    }
+
+    #[test]
+    fn replace_lenient_group_references() {
+        let regex = RegexBuilder::new("(\\d)(\\d)")
+            .lenient_group_references(true)
+            .build()
+            .unwrap();
+        assert_eq!(regex.replace("a12", "$2$1").unwrap(), "a21");
+        assert_eq!(regex.replace("a12", "<$5>").unwrap(), "a<>");
+    }
+
+    #[test]
+    fn match_word_boundary_at_input_edges() {
+        match_result(match_pattern("cat sat", "\\bcat"), true);
+        match_result(match_pattern("a cat", "cat\\b"), true);
+        match_result(match_pattern("cat", "\\bcat\\b"), true);
+        match_result(match_pattern("cats", "cat\\b"), false);
+        match_result(match_pattern("scat", "\\bcat"), false);
+        match_result(match_pattern("", "\\b"), false);
+    }
+
+    #[test]
+    fn matches_with_line_and_column() {
+        let regex = Regex::new("\\d+").unwrap();
+        let reader = Cursor::new("a 1\nnone\n22 and 333\nä4\n");
+        assert_eq!(
+            regex.matches_with_position(reader).unwrap(),
+            [
+                (1, 3, "1".to_string()),
+                (3, 1, "22".to_string()),
+                (3, 8, "333".to_string()),
+                (4, 2, "4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_literal_dot_in_bracket_group() {
+        match_result(match_pattern(".", "[.]"), true);
+        match_result(match_pattern("x", "[.]"), false);
+        match_result(match_pattern("x", "."), true);
+        match_result(match_pattern("a.b", "^a[.]b$"), true);
+        match_result(match_pattern("axb", "^a[.]b$"), false);
+    }
+
+    #[test]
+    fn find_iter_with_literal_prefix() {
+        let regex = Regex::new("http\\w*:").unwrap();
+        let input = "http htt https: x httpx http: ahttp";
+        let matches: Vec<_> = regex.find_iter(input).map(|m| m.start()).collect();
+        assert_eq!(matches, [9, 24]);
+        assert!(!regex.is_match("htt http"));
+        assert!(Regex::new("é+x").unwrap().is_match("aéeééx"));
+        assert_eq!(Regex::new("ab").unwrap().find("aab").unwrap().start(), 1);
+    }
+
+    #[test]
+    fn match_empty_alternatives() {
+        match_result(match_pattern("xyz", "a|"), true);
+        match_result(match_pattern("", "|a"), true);
+        match_result(match_pattern("b", "^(|a)b$"), true);
+        match_result(match_pattern("ab", "^(a||c)b$"), true);
+        let regex = Regex::new("|a").unwrap();
+        assert_eq!(regex.find("a").unwrap().as_str(), "");
+        assert_eq!(Regex::new("a|").unwrap().find("a").unwrap().as_str(), "a");
+    }
+
+    #[test]
+    fn captures_numeric_and_named_groups() {
+        let regex = Regex::new("(?<year>\\d+)-(?P<month>\\d+)(x)?").unwrap();
+        let captures = regex.captures("on 2024-05").unwrap();
+        assert_eq!(captures.len(), 4);
+        assert_eq!(captures.get(0).unwrap().as_str(), "2024-05");
+        assert_eq!(&captures[0], "2024-05");
+        assert_eq!(&captures[1], "2024");
+        assert_eq!(&captures["month"], "05");
+        assert_eq!(captures.name("year").unwrap().start(), 3);
+        assert!(captures.name("day").is_none());
+        assert!(captures.get(3).is_none());
+        assert!(regex.captures("no date").is_none());
+    }
+
+    #[test]
+    fn error_invalid_group_names() {
+        assert!(matches!(
+            Regex::new("a(?<1x>b)"),
+            Err(Error::Parse { position: 1, .. })
+        ));
+        assert!(Regex::new("(?<a>x)(?<a>y)").is_err());
+        assert!(Regex::new("(?<a x)").is_err());
+        assert!(Regex::new("(?=x)").is_err());
+    }
+
+    #[test]
+    fn match_pattern_with_comments() {
+        match_result(match_pattern("ab", "a(?#the letter a)b"), true);
+        match_result(match_pattern("ab", "^a(?#the letter a)b$"), true);
+        match_result(match_pattern("a b", "a(?#the letter a)b"), false);
+        match_result(match_pattern("ab", "(?#only a comment)(a)b"), true);
+        assert!(matches!(
+            Regex::new("a(?#open"),
+            Err(Error::Parse { position: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn match_extended_pattern() {
+        let extended = Regex::new(
+            "(?x)
+            ^ (\\d{4})   # year
+            - (\\d{2})   # month
+            \\ [ ]?      # escaped and bracketed spaces are kept
+            $",
+        )
+        .unwrap();
+        let compact = Regex::new("^(\\d{4})-(\\d{2}) [ ]?$").unwrap();
+        for input in ["2024-05 ", "2024-05  ", "2024-05", "2024 - 05 "] {
+            assert_eq!(
+                extended.is_match(input),
+                compact.is_match(input),
+                "{}",
+                input
+            );
+        }
+        assert!(extended.is_match("2024-05 "));
+    }
+
+    #[test]
+    fn match_ignore_whitespace_option() {
+        let regex = RegexBuilder::new("a b + # comment")
+            .ignore_whitespace(true)
+            .build()
+            .unwrap();
+        assert_eq!(regex.find("xabbb").unwrap().as_str(), "abbb");
+        assert!(Regex::new("a b").unwrap().is_match("a b"));
+    }
+
+    #[test]
+    fn match_digit_class_with_and_without_unicode() {
+        // U+0663 is the Arabic-Indic digit three
+        let input = "\u{663}";
+        let unicode = |pattern: &str| RegexBuilder::new(pattern).unicode(true).build().unwrap();
+        assert!(!Regex::new("\\d").unwrap().is_match(input));
+        assert!(!Regex::new("[\\d]").unwrap().is_match(input));
+        assert!(unicode("\\d").is_match(input));
+        assert!(unicode("[\\d]").is_match(input));
+        assert!(unicode("\\d").is_match("7"));
+        assert!(unicode("[x\\d]").is_match("7"));
+    }
+
+    #[test]
+    fn match_unicode_digit_class_only_decimal_digits() {
+        let unicode = |pattern: &str| RegexBuilder::new(pattern).unicode(true).build().unwrap();
+        for input in ["\u{bd}", "\u{b2}", "\u{216b}"] {
+            assert!(!unicode("\\d").is_match(input));
+            assert!(!unicode("[\\d]").is_match(input));
+            assert!(unicode("\\p{N}").is_match(input));
+        }
+        // U+0966 and U+096F are the Devanagari digits zero and nine, U+0970 is no digit
+        assert!(unicode("^\\d\\d$").is_match("\u{966}\u{96f}"));
+        assert!(!unicode("\\d").is_match("\u{970}"));
+    }
+
+    #[test]
+    fn match_classes_in_bracket_group() {
+        match_result(match_pattern("a.1", "^[\\d.]+$"), false);
+        match_result(match_pattern("3.14", "^[\\d.]+$"), true);
+        match_result(match_pattern("ab-1", "^[\\w-]+$"), true);
+    }
+
+    #[test]
+    fn replace_all_to_writer() {
+        let regex = Regex::new("(\\w+)@(\\w+)").unwrap();
+        let input = "mail bob@home and eve@work.";
+        let mut output = Vec::new();
+        regex.replace_all_to(input, "$2:$1", &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            regex.replace_all(input, "$2:$1").unwrap()
+        );
+        assert!(regex.replace_all_to(input, "$3", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn match_end_anchor_after_repeated_group() {
+        match_result(match_pattern("xabab", "(ab)+$"), true);
+        match_result(match_pattern("ababx", "(ab)+$"), false);
+        match_result(full_match("abab", "(ab)+$"), true);
+        let regex = Regex::new("(ab)+$").unwrap();
+        assert_eq!(regex.find("abxabab").unwrap().start(), 3);
+    }
+
+    #[test]
+    fn matching_lines_of_reader() {
+        let regex = Regex::new("^\\d").unwrap();
+        let reader = Cursor::new("1 one\ntwo\n3 three\n");
+        let lines: Vec<_> = regex.matching_lines(reader).collect::<Result<_>>().unwrap();
+        assert_eq!(lines, ["1 one", "3 three"]);
+
+        let mut lines = regex.matching_lines(Cursor::new(b"1\n\xff\n".to_vec()));
+        assert_eq!(lines.next().unwrap().unwrap(), "1");
+        assert!(matches!(lines.next(), Some(Err(Error::Io(_)))));
+    }
+
+    #[test]
+    fn find_after_keep_out() {
+        let regex = Regex::new("foo\\Kbar").unwrap();
+        let m = regex.find("a foobar").unwrap();
+        assert_eq!((m.start(), m.end()), (5, 8));
+        assert_eq!(m.as_str(), "bar");
+        assert!(!regex.is_match("bar"));
+        assert_eq!(
+            regex.replace_all("foobar foobar", "X").unwrap(),
+            "fooX fooX"
+        );
+    }
+
+    #[test]
+    fn find_keep_out_reset_on_backtracking() {
+        let regex = Regex::new("(a\\Kx|ab)c").unwrap();
+        assert_eq!(regex.find("abc").unwrap().as_str(), "abc");
+        assert_eq!(regex.find("axc").unwrap().as_str(), "xc");
+    }
+
+    #[test]
+    fn keep_out_uses_attempt_start() {
+        let regex = Regex::new("a\\Kb|c").unwrap();
+        assert_eq!(regex.find("ab").unwrap().start(), 1);
+        assert_eq!(regex.matched_alternative("ab"), Some(0));
+
+        let regex = Regex::new("ab\\Kc+").unwrap();
+        assert_eq!(regex.shortest_match("abccc"), Some(3));
+
+        let regex = Regex::new("a\\K").unwrap();
+        let matches: Vec<_> = regex
+            .find_iter("aaa")
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        assert_eq!(matches, [(1, 1), (2, 2), (3, 3)]);
+
+        let regex = Regex::new("a?\\K").unwrap();
+        let matches: Vec<_> = regex
+            .find_iter("ab")
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        assert_eq!(matches, [(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn supported_features_inventory() {
+        let features = supported_features();
+        for feature in [
+            "anchors",
+            "quantifiers",
+            "groups",
+            "backreferences",
+            "lazy quantifiers",
+        ] {
+            assert!(features.contains(&feature), "{}", feature);
+        }
+        for feature in ["lookaround", "recursion"] {
+            assert!(!features.contains(&feature), "{}", feature);
+        }
+    }
+
+    #[test]
+    fn match_negated_bracket_ranges() {
+        match_result(match_pattern("A", "[^a-z0-9]"), true);
+        match_result(match_pattern("m", "[^a-z0-9]"), false);
+        match_result(match_pattern("5", "[^a-z0-9]"), false);
+        match_result(match_pattern("ab5-", "[^a-z0-9]"), true);
+    }
+
+    #[test]
+    fn match_repeated_anchors() {
+        for input in ["log in", "login", "a log", ""] {
+            assert_eq!(
+                match_pattern(input, "^^log").unwrap(),
+                match_pattern(input, "^log").unwrap(),
+                "{}",
+                input
+            );
+        }
+        for input in ["hotdog", "dog house", "dog", ""] {
+            assert_eq!(
+                match_pattern(input, "dog$$").unwrap(),
+                match_pattern(input, "dog$").unwrap(),
+                "{}",
+                input
+            );
+        }
+        match_result(match_pattern("log", "^^log$$"), true);
+    }
+
+    #[test]
+    fn match_prefix_remainder() {
+        let regex = Regex::new("\\d+").unwrap();
+        assert_eq!(regex.match_prefix("123abc"), Some("abc"));
+        assert_eq!(regex.match_prefix("123"), Some(""));
+        assert_eq!(regex.match_prefix("abc123"), None);
+    }
+
+    #[test]
+    fn match_literal_caret_in_bracket_group() {
+        match_result(match_pattern("^", "[a^]"), true);
+        match_result(match_pattern("a", "[a^]"), true);
+        match_result(match_pattern("b", "[a^]"), false);
+        match_result(match_pattern("^", "[^a^]"), false);
+        match_result(match_pattern("a", "[^a^]"), false);
+        match_result(match_pattern("b", "[^a^]"), true);
+    }
+
+    #[test]
+    fn match_multi_line_anchors() {
+        let input = "first\nsecond\nthird";
+        assert!(!Regex::new("^second$").unwrap().is_match(input));
+
+        let regex = RegexBuilder::new("^second$")
+            .multi_line(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match(input));
+        assert_eq!(regex.find(input).unwrap().start(), 6);
+        let regex = RegexBuilder::new("^third$")
+            .multi_line(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match(input));
+        let regex = RegexBuilder::new("^cond").multi_line(true).build().unwrap();
+        assert!(!regex.is_match(input));
+    }
+
+    #[test]
+    fn match_numbers_in_range() {
+        assert!(match_number_range("port 8080", 1024, 65535));
+        assert!(match_number_range("port 1024", 1024, 65535));
+        assert!(match_number_range("port 65535", 1024, 65535));
+        assert!(!match_number_range("port 80", 1024, 65535));
+        assert!(!match_number_range("port 65536", 1024, 65535));
+        assert!(match_number_range("from 80 to 443", 100, 500));
+        assert!(!match_number_range("99999999999999999999999", 0, u64::MAX));
+        assert!(!match_number_range("no numbers", 0, 10));
+    }
+
+    #[test]
+    fn trim_leading_and_trailing_matches() {
+        let regex = Regex::new("[ \t]+").unwrap();
+        assert_eq!(regex.trim_matches("  hello  world \t"), "hello  world");
+        assert_eq!(regex.trim_matches("   "), "");
+        assert_eq!(regex.trim_matches("plain"), "plain");
+
+        let regex = Regex::new("\\d+-?").unwrap();
+        assert_eq!(regex.trim_matches("12-34-ab12cd-56-78"), "ab12cd-");
+
+        let regex = Regex::new("aa").unwrap();
+        assert_eq!(regex.trim_matches("baaa"), "baaa".trim_end_matches("aa"));
+        assert_eq!(regex.trim_matches("aaab"), "aaab".trim_start_matches("aa"));
+    }
+
+    #[test]
+    fn max_match_len_of_patterns() {
+        let max_match_len = |pattern: &str| Regex::new(pattern).unwrap().max_match_len();
+        assert_eq!(max_match_len("abc"), Some(3));
+        assert_eq!(max_match_len("a{2,4}"), Some(4));
+        assert_eq!(max_match_len("a+"), None);
+        assert_eq!(max_match_len("^(ab|c)\\1?$"), Some(4));
+        assert_eq!(max_match_len("(a|b*)c"), None);
+    }
+
+    #[test]
+    fn find_matches_at_word_starts() {
+        let regex = Regex::new("log\\w*").unwrap();
+        let input = "login blog logs catalog";
+        let words: Vec<_> = regex.find_word_starts(input).map(|m| m.as_str()).collect();
+        assert_eq!(words, ["login", "logs"]);
+        assert_eq!(regex.find_word_starts(input).nth(1).unwrap().start(), 11);
+        assert_eq!(regex.find_iter(input).count(), 4);
+    }
+
+    #[test]
+    fn match_unicode_properties() {
+        let unicode = |pattern: &str| RegexBuilder::new(pattern).unicode(true).build().unwrap();
+        assert!(unicode("\\p{L}").is_match("é"));
+        assert!(!unicode("\\p{L}").is_match("7"));
+        assert!(unicode("\\p{N}").is_match("7"));
+        assert!(unicode("\\p{N}").is_match("٣"));
+        assert!(!unicode("\\p{N}").is_match("x"));
+        assert!(unicode("\\p{P}").is_match("«"));
+        assert!(!unicode("\\p{P}").is_match("a1"));
+        assert!(unicode("^[\\p{L}\\p{N}]+$").is_match("naïve42"));
+
+        // without unicode mode the properties only match ascii characters
+        match_result(match_pattern("é", "\\p{L}"), false);
+        match_result(match_pattern("e", "\\p{L}"), true);
+
+        let error = Regex::new("a\\p{X}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown unicode property 'X' at position 1"
+        );
+        assert!(Regex::new("\\p{L").is_err());
+    }
+
+    #[test]
+    fn match_str_and_byte_inputs() {
+        let regex = Regex::new("(\\d+)-\\1").unwrap();
+        assert!(regex.is_match_input("id 42-42"));
+        assert!(regex.is_match_input(b"id 42-42".as_slice()));
+        assert!(!regex.is_match_input("id 42-43"));
+        assert!(!regex.is_match_input(b"id 42-43".as_slice()));
+
+        // byte inputs may start a match inside of a character, strings may not
+        let regex = Regex::from_bytes(b"\xa9").unwrap();
+        assert!(!regex.is_match_input("©"));
+        assert!(regex.is_match_input(b"\xa9".as_slice()));
+    }
+
+    #[test]
+    fn explain_near_match() {
+        let regex = Regex::new("error: \\d+ files").unwrap();
+        assert_eq!(
+            regex.explain_match("error: 12 filez"),
+            "No match, the furthest attempt failed at literal 's' before \"z\" (position 14)"
+        );
+        assert_eq!(
+            regex.explain_match("error: 3 files"),
+            "Matched \"error: 3 files\" at 0..14"
+        );
+    }
+
+    #[test]
+    fn find_leftmost_longest_anchored_alternative() {
+        let regex = Regex::new("^(a|ab)").unwrap();
+        assert_eq!(regex.find("abc").unwrap().as_str(), "a");
+
+        let regex = RegexBuilder::new("^(a|ab)")
+            .leftmost_longest(true)
+            .build()
+            .unwrap();
+        assert_eq!(regex.find("abc").unwrap().as_str(), "ab");
+        assert_eq!(&regex.captures("abc").unwrap()[1], "ab");
+        assert!(regex.find("cab").is_none());
+    }
+
+    #[test]
+    fn matched_alternative_of_leftmost_longest() {
+        let regex = RegexBuilder::new("a|ab")
+            .leftmost_longest(true)
+            .build()
+            .unwrap();
+        assert_eq!(regex.find("ab").unwrap().as_str(), "ab");
+        assert_eq!(regex.matched_alternative("ab"), Some(1));
+        assert_eq!(regex.matched_alternative("ac"), Some(0));
+        assert_eq!(
+            Regex::new("a|ab").unwrap().matched_alternative("ab"),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn replace_all_swaps_captured_fields() {
+        let regex = Regex::new("(\\w+) (\\w+)").unwrap();
+        assert_eq!(
+            regex.replace_all("John Smith", "$2 $1").unwrap(),
+            "Smith John"
+        );
+        assert_eq!(
+            regex.replace_all("John Smith, Jane Doe", "$2 $1").unwrap(),
+            "Smith John, Doe Jane"
+        );
+    }
+
+    #[test]
+    fn match_bracket_group_after_literal() {
+        // the c directly after the a matches, the group is not searched anywhere after it
+        match_result(match_pattern("acb", "a[bc]"), true);
+        match_result(match_pattern("axb", "a[bc]"), false);
+        match_result(match_pattern("abx", "a[bc]"), true);
+        match_result(match_pattern("xab", "^a[bc]"), false);
+    }
+
+    #[test]
+    fn match_in_byte_range() {
+        let regex = Regex::new("^\\d+$").unwrap();
+        let input = "id=42;name=bob7";
+        assert!(regex.is_match_in(input, 3..5));
+        assert!(!regex.is_match_in(input, 0..5));
+        assert!(!regex.is_match_in(input, 11..15));
+        assert!(!regex.is_match_in(input, 3..20));
+
+        let regex = Regex::new("é").unwrap();
+        assert!(regex.is_match_in("aéb", 1..3));
+        assert!(!regex.is_match_in("aéb", 2..3));
+    }
+
+    #[test]
+    fn match_negated_classes_in_bracket_groups() {
+        match_result(match_pattern("a", "[^\\d]"), true);
+        match_result(match_pattern("5", "[^\\d]"), false);
+        match_result(match_pattern("-", "[^\\w]"), true);
+        match_result(match_pattern("x", "[^\\w]"), false);
+        match_result(match_pattern("x9", "^[^\\d][^\\w]"), false);
+    }
+
+    #[test]
+    fn owned_matches() {
+        let regex = Regex::new("\\d+").unwrap();
+        assert_eq!(regex.matches_owned("1 22"), ["1", "22"]);
+        assert!(regex.matches_owned("none").is_empty());
+    }
+
+    #[test]
+    fn tokenize_until_first_gap() {
+        let regex = Regex::new("\\d").unwrap();
+        let tokens: Vec<_> = regex.tokenize("123abc4").map(|m| m.as_str()).collect();
+        assert_eq!(tokens, ["1", "2", "3"]);
+        assert_eq!(regex.tokenize("abc123").count(), 0);
+
+        let regex = Regex::new("\\d+|[a-z]+| ").unwrap();
+        let tokens: Vec<_> = regex.tokenize("let x 42").map(|m| m.as_str()).collect();
+        assert_eq!(tokens, ["let", " ", "x", " ", "42"]);
+    }
+
+    #[test]
+    fn match_code_point_escapes() {
+        match_result(match_pattern("A", "\\u{41}"), true);
+        match_result(match_pattern("B", "\\u{41}"), false);
+        match_result(match_pattern("smile 😀", "\\u{1F600}$"), true);
+        match_result(match_pattern("é", "^[\\u{e0}-\\u{ff}]$"), true);
+
+        let error = Regex::new("\\u{D800}").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid code point 'D800' at position 0");
+        assert!(Regex::new("\\u{110000}").is_err());
+        assert!(Regex::new("\\u{}").is_err());
+        assert!(Regex::new("\\u{41").is_err());
+        assert!(Regex::new("\\u41").is_err());
+    }
+
+    #[test]
+    fn capture_last_repetition_of_alternation() {
+        let regex = Regex::new("(a|b)+").unwrap();
+        let captures = regex.captures("abba").unwrap();
+        assert_eq!(&captures[1], "a");
+        assert_eq!(captures.get(1).unwrap().start(), 3);
+        let whole = captures.get(0).unwrap();
+        assert_eq!((whole.start(), whole.end()), (0, 4));
+
+        let captures = regex.captures("xabbc").unwrap();
+        assert_eq!(&captures[1], "b");
+        assert_eq!(&captures[0], "abb");
+    }
+
+    #[test]
+    fn split_at_multi_byte_delimiters() {
+        let regex = Regex::new("\\s*,\\s*").unwrap();
+        assert_eq!(regex.split("a,  b , c"), ["a", "b", "c"]);
+        assert_eq!(regex.split("ä ,\tö,ü"), ["ä", "ö", "ü"]);
+        assert_eq!(Regex::new(", ").unwrap().split("x, y"), ["x", "y"]);
+        assert_eq!(regex.split("single"), ["single"]);
+
+        match_result(match_pattern("a\tb", "a\\sb"), true);
+        match_result(match_pattern("a-b", "a[\\s-]b"), true);
+        match_result(match_pattern("ab", "a\\sb"), false);
+    }
+
+    #[test]
+    fn find_with_first_byte_prefilter() {
+        let input = format!("{}x12", "a".repeat(1000));
+        let regex = Regex::new("[xyz]\\d+").unwrap();
+        assert_eq!(regex.find(&input).unwrap().start(), 1000);
+        assert!(!regex.is_match(&"a".repeat(1000)));
+
+        let regex = Regex::new("(cat|dog)s?|\\d").unwrap();
+        let found: Vec<_> = regex
+            .find_iter("a dogs, 7 cats")
+            .map(|m| m.as_str())
+            .collect();
+        assert_eq!(found, ["dogs", "7", "cats"]);
+
+        // nullable patterns and non ascii starts are not skipped
+        assert_eq!(Regex::new("x*").unwrap().find("abc").unwrap().start(), 0);
+        assert_eq!(
+            Regex::new("[^a]").unwrap().find("aé").unwrap().as_str(),
+            "é"
+        );
+        assert_eq!(
+            Regex::new("\\w+$").unwrap().find("a b").unwrap().as_str(),
+            "b"
+        );
+        assert!(Regex::new(".").unwrap().is_match_bytes(b"\xff"));
+        assert!(Regex::new("(a)\\1").unwrap().is_match("xaa"));
+    }
+
+    #[test]
+    fn match_end_anchor_after_optional_character() {
+        match_result(match_pattern("color", "colou?r$"), true);
+        match_result(match_pattern("my colour", "colou?r$"), true);
+        match_result(match_pattern("colours", "colou?r$"), false);
+    }
+
+    #[test]
+    fn patterns_matching_empty_input() {
+        let matches_empty = |pattern: &str| Regex::new(pattern).unwrap().matches_empty();
+        for pattern in ["a*", "(abc)?", "^$", "a|", "(a*)\\1", "\\b", "x{0,3}"] {
+            assert!(matches_empty(pattern), "{}", pattern);
+        }
+        for pattern in ["a", "a+", "(a|b)c*", "^.$", "(a)\\1", "x{1,3}", "(a|\\1)"] {
+            assert!(!matches_empty(pattern), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn match_fixed_strings() {
+        let regex = RegexBuilder::new("^a.b($")
+            .fixed_strings(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("x ^a.b($ y"));
+        assert!(!regex.is_match("axb("));
+    }
+
+    #[test]
+    fn named_captures_map() {
+        let regex = Regex::new("(?<key>\\w+)=(\\d+)?(?P<value>\\w+)").unwrap();
+        let captures = regex.named_captures("set color=red").unwrap();
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures["key"], "color");
+        assert_eq!(captures["value"], "red");
+        assert!(regex.named_captures("no pairs").is_none());
+
+        let regex = Regex::new("(?<a>a)|(?<b>b)").unwrap();
+        let captures = regex.named_captures("b").unwrap();
+        assert_eq!(captures.keys().collect::<Vec<_>>(), ["b"]);
+    }
+
+    #[test]
+    fn match_optional_group_present_or_absent() {
+        match_result(match_pattern("xyz", "(abc)?xyz"), true);
+        match_result(match_pattern("abcxyz", "^(abc)?xyz$"), true);
+        match_result(match_pattern("abxyz", "^(abc)?xyz$"), false);
+
+        let regex = Regex::new("(abc)?xyz").unwrap();
+        let captures = regex.captures("xyz").unwrap();
+        assert!(captures.get(1).is_none());
+        assert_eq!(&captures[0], "xyz");
+        assert_eq!(&regex.captures("abcxyz").unwrap()[1], "abc");
+    }
+
+    #[test]
+    fn reject_unbalanced_brackets_and_parentheses() {
+        assert!(matches!(
+            Regex::new("a[bc"),
+            Err(Error::UnbalancedBracket { position: 1 })
+        ));
+        assert!(matches!(
+            Regex::new("(ab"),
+            Err(Error::UnbalancedParen { position: 0 })
+        ));
+        assert!(matches!(
+            Regex::new("ab)"),
+            Err(Error::UnbalancedParen { position: 2 })
+        ));
+        assert!(matches!(
+            Regex::new("(a(b)"),
+            Err(Error::UnbalancedParen { position: 0 })
+        ));
+        let error = match_pattern("abc", "a[bc").unwrap_err();
+        assert_eq!(error.to_string(), "Unbalanced bracket at position 1");
+    }
+
+    #[test]
+    fn match_whole_words() {
+        let regex = RegexBuilder::new("(c)at|d\\w+")
+            .whole_words(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("the cat sat"));
+        assert!(!regex.is_match("category"));
+        assert_eq!(regex.find("a dogma").unwrap().as_str(), "dogma");
+        assert_eq!(&regex.captures("cat").unwrap()[1], "c");
+    }
+
+    #[test]
+    fn matched_alternative_of_whole_words() {
+        let regex = RegexBuilder::new("cat|dog")
+            .whole_words(true)
+            .build()
+            .unwrap();
+        assert_eq!(regex.matched_alternative("a dog"), Some(1));
+        assert_eq!(regex.matched_alternative("a cat"), Some(0));
+        assert_eq!(regex.matched_alternative("hotdog"), None);
+    }
+
+    #[test]
+    fn first_match_line_stops_reading_early() {
+        /// Reader failing on every read, it must never be reached after the match
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read after the match"))
+            }
+        }
+
+        let lines = format!("{}needle\n", "hay\n".repeat(100_000));
+        let reader = std::io::BufReader::new(std::io::Read::chain(Cursor::new(lines), Failing));
+        let regex = Regex::new("needle").unwrap();
+        let found = regex.first_match_line(reader).unwrap();
+        assert_eq!(found, Some((100_001, "needle".to_string())));
+
+        // the last line is matched without a trailing newline
+        let lines = format!("{}needle", "hay\n".repeat(1000));
+        let found = regex.first_match_line(Cursor::new(lines)).unwrap();
+        assert_eq!(found, Some((1001, "needle".to_string())));
+    }
+
+    #[test]
+    fn match_embedded_negated_bracket_group() {
+        match_result(match_pattern("axc", "a[^b]c"), true);
+        match_result(match_pattern("abc", "a[^b]c"), false);
+        match_result(match_pattern("xbc", "^[^a]bc"), true);
+        match_result(match_pattern("abc", "^[^a]bc"), false);
+        match_result(match_pattern("abx", "ab[^c]$"), true);
+        match_result(match_pattern("abc", "ab[^c]$"), false);
+    }
 }
@@ -0,0 +1,19 @@
+mod grep;
+
+pub use grep::capture_table;
+pub use grep::compile_any;
+pub use grep::escape;
+pub use grep::full_match;
+pub use grep::match_bytes;
+pub use grep::match_number_range;
+pub use grep::match_pattern;
+pub use grep::supported_features;
+pub use grep::CaptureMatches;
+pub use grep::Captures;
+pub use grep::Error;
+pub use grep::Input;
+pub use grep::Match;
+pub use grep::Matches;
+pub use grep::Regex;
+pub use grep::RegexBuilder;
+pub use grep::SplitItem;
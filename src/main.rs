@@ -1,32 +1,34 @@
-use anyhow::Context;
 use anyhow::Result;
-use grep::match_pattern;
 use std::env;
 use std::io;
+use std::io::Read;
 use std::process;
 
-mod grep;
+mod cli;
 
-// Usage: echo <input_text> | your_program.sh -E <pattern>
+// Usage: echo <input_text> | your_program.sh [-d] [-E|-F|-G] [-r] [-w] [-v] [-o] [-b] [-q] [-l|-L] [--multiline] <pattern> [<file>...]
+// A <pattern> of - is read from the first line of the standard input, the rest is the input
 fn main() -> Result<()> {
-    // You can use print statements as follows for debugging, they'll be visible when running tests.
-    println!("Logs from your program will appear here!");
+    let options = cli::Options::parse(env::args().skip(1))?;
 
-    if env::args().nth(1).context("no input found")? != "-E" {
-        println!("Expected first argument to be '-E'");
-        process::exit(1);
-    }
-
-    let pattern = env::args().nth(2).context("no pattern found")?;
-    let mut input_line = String::new();
-
-    io::stdin().read_line(&mut input_line)?;
-
-    if match_pattern(&input_line, &pattern)? {
-        println!("pattern was matching");
+    if cli::run(&options, unbuffered_stdin()?, &mut io::stdout())? {
         process::exit(0)
     } else {
-        println!("pattern is not matching");
         process::exit(1)
     }
 }
+
+/// Returns the standard input without the 8 KiB buffer of `io::Stdin`, which reads that much
+/// for every smaller read, so the configured buffer size is the only buffer
+#[cfg(unix)]
+fn unbuffered_stdin() -> Result<impl Read> {
+    use std::os::fd::AsFd;
+    Ok(std::fs::File::from(
+        io::stdin().as_fd().try_clone_to_owned()?,
+    ))
+}
+
+#[cfg(not(unix))]
+fn unbuffered_stdin() -> Result<impl Read> {
+    Ok(io::stdin())
+}